@@ -204,10 +204,10 @@ fn criterion_benchmark(c: &mut Criterion) {
     });
 
     if let Ps::Loaded(state) = &mut ps {
-        state.edit.copied_curve = Some(Rc::new(RefCell::new(Curve {
+        state.edit.copied_curves = vec![Rc::new(RefCell::new(Curve {
             shape: Rectangle::default().into(),
             ..Curve::default()
-        })));
+        }))];
         ps.view();
     }
 
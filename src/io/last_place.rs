@@ -3,6 +3,8 @@ use std::{fs, fs::File, io::BufReader, path::PathBuf};
 use serde::{Deserialize, Serialize};
 
 use crate::ui::curve::*;
+use crate::ui::layer::Layer;
+use crate::ui::named_style::NamedStyle;
 
 const FILE_NAME: &str = "last_place";
 
@@ -15,6 +17,10 @@ pub struct SavedState {
     pub on_view: Option<usize>,
     //edit
     pub curves: Vec<Curve>,
+    #[serde(default)]
+    pub styles: Vec<NamedStyle>,
+    #[serde(default)]
+    pub layers: Vec<Layer>,
 }
 
 pub async fn save_state(saved_state: SavedState, path: PathBuf) -> std::io::Result<()> {
@@ -20,16 +20,29 @@ pub mod io {
 pub mod ui {
     pub mod curve;
     pub mod edit;
+    pub mod effect;
+    mod eyedropper;
+    pub mod gradient;
+    pub mod group;
+    pub mod history;
     mod icons;
+    pub mod layer;
+    pub mod named_style;
     pub mod shape;
+    pub mod snap;
     pub mod style;
     pub mod toolbar;
+    pub mod transform;
     pub mod utils;
     pub mod viewer;
+    pub mod viewport;
     pub mod welcome;
 
     pub use curve::*;
     pub use edit::*;
+    pub use effect::*;
+    pub use gradient::*;
+    pub use named_style::*;
     pub use shape::*;
     pub use toolbar::*;
     pub use viewer::*;
@@ -191,6 +204,8 @@ impl Application for Ps {
                             images,
                             on_view,
                             curves,
+                            styles,
+                            layers,
                         } = state;
                         *self = Ps::Loaded(Box::new(State {
                             viewer: Viewer {
@@ -203,6 +218,8 @@ impl Application for Ps {
                                     .into_iter()
                                     .map(|curve| Rc::new(RefCell::new(curve)))
                                     .collect(),
+                                styles,
+                                layers,
                             ),
                             is_editing,
                             ..State::default()
@@ -230,11 +247,14 @@ impl Application for Ps {
                     }
                     ToolbarMessage::New => {
                         state.is_editing = true;
+                        state.viewer.picking = false;
                     }
 
                     //edit
+                    //吸管打开时回到图片上取色
                     ToolbarMessage::Back => {
                         state.is_editing = false;
+                        state.viewer.picking = state.edit.is_picking();
                     }
                     ToolbarMessage::Export => state.edit.export(),
                     ToolbarMessage::Edit(em) => state.edit.update(em),
@@ -300,6 +320,13 @@ impl Application for Ps {
                     },
                     _ => {}
                 },
+                Message::Viewer(ViewerMessage::PixelPicked(x, y)) => {
+                    if let Some(color) = state.viewer.pixel(x, y) {
+                        state.edit.update(EditMessage::ColorPicked(color));
+                    }
+                    state.viewer.picking = false;
+                    state.is_editing = true;
+                }
                 Message::Viewer(vm) => state.viewer.update(vm),
                 Message::Edit(em) => state.edit.update(em),
 
@@ -318,6 +345,8 @@ impl Application for Ps {
                                     .into_iter()
                                     .map(|mut rc| Rc::make_mut(&mut rc).to_owned().into_inner())
                                     .collect(),
+                                styles: state.edit.styles.clone(),
+                                layers: state.edit.layers.clone(),
                             };
                             return Command::perform(
                                 save_state(saved_state, parent.to_path_buf()),
@@ -41,12 +41,22 @@ mod io {
 mod ui {
     pub mod curve;
     pub mod edit;
+    pub mod effect;
+    mod eyedropper;
+    pub mod gradient;
+    pub mod group;
+    pub mod history;
     mod icons;
+    pub mod layer;
+    pub mod named_style;
     pub mod shape;
+    pub mod snap;
     pub mod style;
     pub mod toolbar;
+    pub mod transform;
     pub mod utils;
     pub mod viewer;
+    pub mod viewport;
     pub mod welcome;
 
     pub use edit::*;
@@ -211,6 +221,8 @@ impl Application for Ps {
                             images,
                             on_view,
                             curves,
+                            styles,
+                            layers,
                         } = state;
                         *self = Ps::Loaded(Box::new(State {
                             viewer: Viewer {
@@ -223,6 +235,8 @@ impl Application for Ps {
                                     .into_iter()
                                     .map(|curve| Rc::new(RefCell::new(curve)))
                                     .collect(),
+                                styles,
+                                layers,
                             ),
                             is_editing,
                             ..State::default()
@@ -244,6 +258,13 @@ impl Application for Ps {
             },
             Ps::Loaded(state) => match message {
                 //将事件传递到下一级进行处理
+                Message::Viewer(ViewerMessage::PixelPicked(x, y)) => {
+                    if let Some(color) = state.viewer.pixel(x, y) {
+                        state.edit.update(EditMessage::ColorPicked(color));
+                    }
+                    state.viewer.picking = false;
+                    state.is_editing = true;
+                }
                 Message::Viewer(vm) => state.viewer.update(vm),
                 Message::Edit(em) => state.edit.update(em),
                 //工具栏的事件要在这里处理
@@ -257,11 +278,14 @@ impl Application for Ps {
                     }
                     ToolbarMessage::New => {
                         state.is_editing = true;
+                        state.viewer.picking = false;
                     }
 
                     //edit
+                    //吸管打开时回到图片上取色
                     ToolbarMessage::Back => {
                         state.is_editing = false;
+                        state.viewer.picking = state.edit.is_picking();
                     }
                     ToolbarMessage::Export => state.edit.export(),
                     ToolbarMessage::Edit(em) => state.edit.update(em),
@@ -346,6 +370,8 @@ impl Application for Ps {
                                     .into_iter()
                                     .map(|mut rc| Rc::make_mut(&mut rc).to_owned().into_inner())
                                     .collect(),
+                                styles: state.edit.styles.clone(),
+                                layers: state.edit.layers.clone(),
                             };
                             return Command::perform(
                                 save_state(saved_state, parent.to_path_buf()),
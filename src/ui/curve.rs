@@ -1,15 +1,31 @@
 use iced::{
-    canvas::{Fill, Frame, LineCap, LineDash, LineJoin, Stroke},
-    Color, Point,
+    canvas::{
+        Fill, FillRule, Frame, LineCap, LineDash, LineJoin, Path, Stroke, Text as CanvasText,
+    },
+    Color, Point, Rectangle, Size, Vector,
 };
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
-use svg::node::element::Path as SvgPath;
+use svg::node::element::path::Data;
+use svg::node::element::{Element, Path as SvgPath};
+use svg::Node;
 
-use std::fmt::Display;
+use std::{
+    cell::{Ref, RefCell},
+    collections::HashMap,
+    fmt::Display,
+};
 
-use super::shape::{Line, Shape, ShapeEnum, ShapeMessage};
-use crate::utils::{get_format_color, is_valid_rgb, SerdeColor};
+use super::effect::{BlendMode, Shadow, ShadowMessage};
+use super::gradient::{Gradient, GradientMessage, Paint};
+use super::group::Group;
+use super::named_style::class_name;
+use super::shape::{Line, Marker, Shape, ShapeEnum, ShapeMessage};
+use super::transform::Transform;
+use crate::utils::{
+    data_to_path, get_format_rgb, is_valid_rgb, miter_joins, parse_color, path_bounds,
+    segment_midpoints, MiterJoin, SerdeColor,
+};
 
 #[derive(Debug, Clone)]
 pub enum CurveMessage {
@@ -30,7 +46,85 @@ pub enum CurveMessage {
     LineCapSelected(EqLineCap),
     LineJoinSelected(EqLineJoin),
 
+    FillToggled(bool),
+
+    InputFillR(String),
+    InputFillG(String),
+    InputFillB(String),
+    InputFillA(String),
+
+    SlideFillR(f32),
+    SlideFillG(f32),
+    SlideFillB(f32),
+    SlideFillA(f32),
+
+    FillRuleSelected(EqFillRule),
+
+    InputSegments(String),
+    InputOffset(String),
+    DashPresetSelected(DashPreset),
+
+    InputMiterLimit(String),
+    PaintOrderSelected(PaintOrder),
+    NonScalingStrokeToggled(bool),
+
+    SlideOpacity(f32),
+    BlendModeSelected(BlendMode),
+    ShadowToggled(bool),
+    Shadow(ShadowMessage),
+
+    StrokePaintSelected(Paint),
+    FillPaintSelected(Paint),
+    StrokeGradient(GradientMessage),
+    FillGradient(GradientMessage),
+
+    //颜色选择器
+    SetColor(ColorTarget, Color),
+    //色板和吸管一次选定的颜色，不和滑条的拖动合并
+    SwatchPicked(ColorTarget, Color),
+    InputHex(ColorTarget, String),
+    EyedropperToggled(ColorTarget),
+    ColorSpaceSelected(ColorSpace),
+
     CurveSelected(usize),
+
+    //变换矩阵已经包含了变换的中心
+    Transform(Transform),
+    //松开滑条，之后的修改不再合并到这次拖动里
+    Released,
+}
+
+//颜色选择器修改的是描边还是填充
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorTarget {
+    Stroke,
+    Fill,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    #[default]
+    Rgb,
+    Hsl,
+    Hsv,
+}
+
+impl ColorSpace {
+    pub const ALL: [ColorSpace; 3] = [ColorSpace::Rgb, ColorSpace::Hsl, ColorSpace::Hsv];
+}
+
+impl Display for ColorSpace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ColorSpace::Rgb => "RGB",
+                ColorSpace::Hsl => "HSL",
+                ColorSpace::Hsv => "HSV",
+            }
+        )
+    }
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -97,8 +191,117 @@ impl Display for EqLineJoin {
     }
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EqFillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+impl EqFillRule {
+    pub const ALL: [EqFillRule; 2] = [EqFillRule::NonZero, EqFillRule::EvenOdd];
+}
+
+impl From<EqFillRule> for FillRule {
+    fn from(rule: EqFillRule) -> Self {
+        match rule {
+            EqFillRule::NonZero => FillRule::NonZero,
+            EqFillRule::EvenOdd => FillRule::EvenOdd,
+        }
+    }
+}
+
+impl Display for EqFillRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                EqFillRule::NonZero => "nonzero",
+                EqFillRule::EvenOdd => "evenodd",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DashPreset {
+    Solid,
+    Dotted,
+    Dashed,
+    DashDot,
+}
+
+impl DashPreset {
+    pub const ALL: [DashPreset; 4] = [
+        DashPreset::Solid,
+        DashPreset::Dotted,
+        DashPreset::Dashed,
+        DashPreset::DashDot,
+    ];
+
+    //预设的长度是线宽的倍数
+    pub fn segments(&self, width: f32) -> Vec<f32> {
+        let pattern: &[f32] = match self {
+            DashPreset::Solid => &[],
+            DashPreset::Dotted => &[1.0, 2.0],
+            DashPreset::Dashed => &[4.0, 2.0],
+            DashPreset::DashDot => &[4.0, 2.0, 1.0, 2.0],
+        };
+        pattern.iter().map(|length| length * width).collect()
+    }
+
+    pub fn matching(segments: &[f32], width: f32) -> Option<DashPreset> {
+        DashPreset::ALL
+            .into_iter()
+            .find(|preset| preset.segments(width) == segments)
+    }
+}
+
+impl Display for DashPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                DashPreset::Solid => "solid",
+                DashPreset::Dotted => "dotted",
+                DashPreset::Dashed => "dashed",
+                DashPreset::DashDot => "dash-dot",
+            }
+        )
+    }
+}
+
+//填充、描边和箭头的绘制顺序，和svg的paint-order对应
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaintOrder {
+    #[default]
+    Normal,
+    Stroke,
+    Markers,
+}
+
+impl PaintOrder {
+    pub const ALL: [PaintOrder; 3] = [PaintOrder::Normal, PaintOrder::Stroke, PaintOrder::Markers];
+}
+
+impl Display for PaintOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                PaintOrder::Normal => "normal",
+                PaintOrder::Stroke => "stroke",
+                PaintOrder::Markers => "markers",
+            }
+        )
+    }
+}
+
 #[serde_as]
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Curve {
     pub shape: ShapeEnum,
     #[serde_as(as = "SerdeColor")]
@@ -106,8 +309,65 @@ pub struct Curve {
     pub width: f32,
     pub line_cap: EqLineCap,
     pub line_join: EqLineJoin,
-    // pub segments: Vec<f32>,
-    // pub offset: usize,
+    //为None时不填充
+    #[serde_as(as = "Option<SerdeColor>")]
+    #[serde(default)]
+    pub fill: Option<Color>,
+    #[serde(default)]
+    pub fill_rule: EqFillRule,
+    //不为None时代替对应的纯色
+    #[serde(default)]
+    pub stroke_gradient: Option<Gradient>,
+    #[serde(default)]
+    pub fill_gradient: Option<Gradient>,
+    //虚线的线段和间隔交替排列，为空时是实线
+    #[serde(default)]
+    pub segments: Vec<f32>,
+    //虚线开始的位置，和svg的stroke-dashoffset一样是长度，可以是小数和负数
+    #[serde(default)]
+    pub offset: f32,
+    //正在输入的虚线，输入到一半时可能无法解析
+    #[serde(skip)]
+    pub segments_input: Option<String>,
+    //正在输入的偏移，比如只输入了负号
+    #[serde(skip)]
+    pub offset_input: Option<String>,
+    //正在输入的十六进制或颜色名
+    #[serde(skip)]
+    pub hex_input: Option<(ColorTarget, String)>,
+    //正在输入的图形属性，输入到一半时可能无法解析
+    #[serde(skip)]
+    pub attribute_input: Option<(String, String)>,
+    //引用的共用样式的名字
+    #[serde(default)]
+    pub style: Option<String>,
+    //斜接长度和线宽之比的上限，超过时改用斜角连接
+    #[serde(default = "Curve::default_miter_limit")]
+    pub miter_limit: f32,
+    #[serde(default)]
+    pub paint_order: PaintOrder,
+    //导出后缩放时线宽保持不变
+    #[serde(default)]
+    pub non_scaling_stroke: bool,
+    //整体透明度，和颜色自己的透明度相乘
+    #[serde(default = "Curve::default_opacity")]
+    pub opacity: f32,
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+    #[serde(default)]
+    pub shadow: Option<Shadow>,
+    //所在的组
+    #[serde(default)]
+    pub group: Group,
+    //所在图层的下标
+    #[serde(default)]
+    pub layer: usize,
+    //图形自己没法表示旋转和斜切时记录的变换矩阵，点的坐标都在变换之前
+    #[serde(default)]
+    pub transform: Transform,
+    //画布上补尖角用的拐角，图形或者变换改变时清空，画的时候再算
+    #[serde(skip)]
+    pub miter_joins: RefCell<Option<Vec<MiterJoin>>>,
 }
 
 impl Default for Curve {
@@ -118,18 +378,115 @@ impl Default for Curve {
             width: 2.0,
             line_cap: EqLineCap::Round,
             line_join: EqLineJoin::Round,
-            // segments: line_dash.segments.to_vec(),
-            // offset: line_dash.offset,
+            fill: None,
+            fill_rule: EqFillRule::NonZero,
+            stroke_gradient: None,
+            fill_gradient: None,
+            segments: vec![],
+            offset: 0.0,
+            segments_input: None,
+            offset_input: None,
+            hex_input: None,
+            attribute_input: None,
+            style: None,
+            miter_limit: Curve::default_miter_limit(),
+            paint_order: PaintOrder::Normal,
+            non_scaling_stroke: false,
+            opacity: Curve::default_opacity(),
+            blend_mode: BlendMode::Normal,
+            shadow: None,
+            group: Group::default(),
+            layer: 0,
+            transform: Transform::IDENTITY,
+            miter_joins: RefCell::default(),
         }
     }
 }
 
 impl Curve {
+    const STROKE_GRADIENT: &'static str = "stroke_gradient_";
+    const FILL_GRADIENT: &'static str = "fill_gradient_";
+    //画布上控制点的半径，不随缩放变化
+    const HANDLE_RADIUS: f32 = 5.0;
+
+    fn default_miter_limit() -> f32 {
+        4.0
+    }
+
+    fn default_opacity() -> f32 {
+        1.0
+    }
+
     #[inline(always)]
     pub fn update(&mut self, message: CurveMessage) {
+        self.miter_joins.take();
+        if !matches!(message, CurveMessage::InputHex(..)) {
+            self.hex_input = None;
+        }
+        if !matches!(message, CurveMessage::InputOffset(..)) {
+            self.offset_input = None;
+        }
+        self.attribute_input = match &message {
+            CurveMessage::Shape(ShapeMessage::Attribute(name, input)) => {
+                Some((name.clone(), input.clone()))
+            }
+            _ => None,
+        };
+
+        //单独修改样式里的属性之后，曲线不再跟着样式变化
+        if !matches!(
+            message,
+            CurveMessage::Shape(_)
+                | CurveMessage::FillRuleSelected(_)
+                | CurveMessage::InputMiterLimit(_)
+                | CurveMessage::PaintOrderSelected(_)
+                | CurveMessage::NonScalingStrokeToggled(_)
+                | CurveMessage::SlideOpacity(_)
+                | CurveMessage::BlendModeSelected(_)
+                | CurveMessage::ShadowToggled(_)
+                | CurveMessage::Shadow(_)
+                | CurveMessage::EyedropperToggled(_)
+                | CurveMessage::ColorSpaceSelected(_)
+                | CurveMessage::CurveSelected(_)
+                | CurveMessage::Transform(_)
+        ) {
+            self.style = None;
+        }
+
         match message {
             CurveMessage::Shape(sm) => {
-                self.shape.update(sm);
+                let sm = self.to_local(sm);
+                let translating = matches!(sm, ShapeMessage::Move(..) | ShapeMessage::Centered(_));
+                let anchor = self.anchor();
+
+                match sm {
+                    ShapeMessage::MovePoint(index, point) if self.is_gradient_handle(&index) => {
+                        self.move_gradient_handle(&index, point);
+                    }
+                    //按住渐变的点整体移动时，换成图形自己的点去移动
+                    ShapeMessage::Move(index, point) if self.is_gradient_handle(&index) => {
+                        if let (Some(handle), Some((key, p))) =
+                            (self.local_points().get(&index), &anchor)
+                        {
+                            self.shape
+                                .update(ShapeMessage::Move(key.clone(), *p + (point - *handle)));
+                        }
+                    }
+                    sm => self.shape.update(sm),
+                }
+
+                //图形整体移动时渐变跟着移动
+                if let (true, Some((key, before))) = (translating, anchor) {
+                    if let Some(after) = self.shape.points().get(&key) {
+                        let offset = *after - before;
+                        for gradient in [&mut self.stroke_gradient, &mut self.fill_gradient]
+                            .into_iter()
+                            .flatten()
+                        {
+                            gradient.translate(offset);
+                        }
+                    }
+                }
             }
 
             CurveMessage::InputColorR(r) => {
@@ -179,58 +536,839 @@ impl Curve {
             }
             CurveMessage::LineCapSelected(lc) => self.line_cap = lc,
             CurveMessage::LineJoinSelected(lj) => self.line_join = lj,
+            CurveMessage::FillToggled(enabled) => {
+                self.fill = if enabled { Some(Color::WHITE) } else { None };
+                self.fill_gradient = None;
+            }
+            CurveMessage::InputFillR(r) => {
+                if let (Ok(r), Some(fill)) = (r.parse::<f32>(), &mut self.fill) {
+                    if is_valid_rgb(r) {
+                        fill.r = r / 255.0;
+                    }
+                }
+            }
+            CurveMessage::InputFillG(g) => {
+                if let (Ok(g), Some(fill)) = (g.parse::<f32>(), &mut self.fill) {
+                    if is_valid_rgb(g) {
+                        fill.g = g / 255.0;
+                    }
+                }
+            }
+            CurveMessage::InputFillB(b) => {
+                if let (Ok(b), Some(fill)) = (b.parse::<f32>(), &mut self.fill) {
+                    if is_valid_rgb(b) {
+                        fill.b = b / 255.0;
+                    }
+                }
+            }
+            CurveMessage::InputFillA(a) => {
+                if let (Ok(a), Some(fill)) = (a.parse::<f32>(), &mut self.fill) {
+                    if (0.0..=1.0).contains(&a) {
+                        fill.a = a;
+                    }
+                }
+            }
+            CurveMessage::SlideFillR(r) => {
+                if let Some(fill) = &mut self.fill {
+                    fill.r = r;
+                }
+            }
+            CurveMessage::SlideFillG(g) => {
+                if let Some(fill) = &mut self.fill {
+                    fill.g = g;
+                }
+            }
+            CurveMessage::SlideFillB(b) => {
+                if let Some(fill) = &mut self.fill {
+                    fill.b = b;
+                }
+            }
+            CurveMessage::SlideFillA(a) => {
+                if let Some(fill) = &mut self.fill {
+                    fill.a = a;
+                }
+            }
+            CurveMessage::FillRuleSelected(rule) => self.fill_rule = rule,
+            CurveMessage::InputSegments(input) => {
+                let segments = input
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.parse::<f32>())
+                    .collect::<Result<Vec<f32>, _>>();
+
+                //全是0的虚线画不出来
+                if let Ok(segments) = segments {
+                    if segments.is_empty()
+                        || segments.iter().all(|s| *s >= 0.0) && segments.iter().any(|s| *s > 0.0)
+                    {
+                        self.segments = segments;
+                        self.offset = 0.0;
+                    }
+                }
+                self.segments_input = Some(input);
+            }
+            CurveMessage::InputOffset(input) => {
+                if let Ok(offset) = input.trim().parse::<f32>() {
+                    if offset.is_finite() {
+                        self.offset = offset;
+                    }
+                }
+                self.offset_input = Some(input);
+            }
+            CurveMessage::StrokePaintSelected(paint) => {
+                self.stroke_gradient = self.new_gradient(paint, self.color);
+            }
+            CurveMessage::FillPaintSelected(paint) => {
+                if let Some(fill) = self.fill {
+                    self.fill_gradient = self.new_gradient(paint, fill);
+                }
+            }
+            CurveMessage::StrokeGradient(gm) => {
+                if let Some(gradient) = &mut self.stroke_gradient {
+                    gradient.update(gm);
+                }
+            }
+            CurveMessage::FillGradient(gm) => {
+                if let Some(gradient) = &mut self.fill_gradient {
+                    gradient.update(gm);
+                }
+            }
+            CurveMessage::DashPresetSelected(preset) => {
+                self.segments = preset.segments(self.width);
+                self.offset = 0.0;
+                self.segments_input = None;
+            }
+            //svg要求斜接限制不小于1
+            CurveMessage::InputMiterLimit(limit) => {
+                if let Ok(limit) = limit.parse::<f32>() {
+                    if limit >= 1.0 {
+                        self.miter_limit = limit;
+                    }
+                }
+            }
+            CurveMessage::PaintOrderSelected(order) => self.paint_order = order,
+            CurveMessage::NonScalingStrokeToggled(enabled) => self.non_scaling_stroke = enabled,
+            CurveMessage::SlideOpacity(opacity) => self.opacity = opacity,
+            CurveMessage::BlendModeSelected(mode) => self.blend_mode = mode,
+            CurveMessage::ShadowToggled(enabled) => {
+                self.shadow = if enabled {
+                    Some(Shadow::default())
+                } else {
+                    None
+                };
+            }
+            CurveMessage::Shadow(sm) => {
+                if let Some(shadow) = &mut self.shadow {
+                    shadow.update(sm);
+                }
+            }
+            CurveMessage::SetColor(target, color) | CurveMessage::SwatchPicked(target, color) => {
+                self.set_color(target, color)
+            }
+            CurveMessage::InputHex(target, input) => {
+                if let Some(color) = parse_color(&input) {
+                    self.set_color(target, color);
+                }
+                self.hex_input = Some((target, input));
+            }
+            CurveMessage::Transform(transform) => self.transform(&transform),
             _ => {}
         }
     }
 
+    //能算到点上的图形直接改点，渐变跟着一起变换，其余的图形叠加到变换矩阵上
+    //算到点上时线宽和虚线也要缩放，和叠加到变换矩阵上的效果一致
+    pub fn transform(&mut self, transform: &Transform) {
+        self.miter_joins.take();
+        if self.transform.is_identity() && self.shape.transform(transform) {
+            for gradient in [&mut self.stroke_gradient, &mut self.fill_gradient]
+                .into_iter()
+                .flatten()
+            {
+                gradient.transform(transform);
+            }
+            if !self.non_scaling_stroke {
+                let scale = transform.scale_factor();
+                self.width *= scale;
+                for segment in self.segments.iter_mut() {
+                    *segment *= scale;
+                }
+            }
+        } else {
+            self.transform = self.transform.then(transform);
+        }
+    }
+
+    //画布上的坐标换回图形自己的坐标
+    fn to_local(&self, message: ShapeMessage) -> ShapeMessage {
+        let inverse = match self.transform.invert() {
+            Some(inverse) if !self.transform.is_identity() => inverse,
+            _ => return message,
+        };
+        match message {
+            ShapeMessage::MovePoint(index, point) => {
+                ShapeMessage::MovePoint(index, inverse.apply(point))
+            }
+            ShapeMessage::Move(index, point) => ShapeMessage::Move(index, inverse.apply(point)),
+            ShapeMessage::Centered(point) => ShapeMessage::Centered(inverse.apply(point)),
+            message => message,
+        }
+    }
+
+    //没有开启填充时不会设置填充色
+    pub fn set_color(&mut self, target: ColorTarget, color: Color) {
+        match target {
+            ColorTarget::Stroke => self.color = color,
+            ColorTarget::Fill => {
+                if let Some(fill) = &mut self.fill {
+                    *fill = color;
+                }
+            }
+        }
+    }
+
+    //画布上的点，经过了变换矩阵
+    pub fn points(&self) -> HashMap<String, Point> {
+        let mut points = self.local_points();
+        if !self.transform.is_identity() {
+            for point in points.values_mut() {
+                *point = self.transform.apply(*point);
+            }
+        }
+        points
+    }
+
+    //图形的点加上渐变的点，渐变的点用前缀区分
+    fn local_points(&self) -> HashMap<String, Point> {
+        let mut points = self.shape.points();
+        for (prefix, gradient) in [
+            (Curve::STROKE_GRADIENT, &self.stroke_gradient),
+            (Curve::FILL_GRADIENT, &self.fill_gradient),
+        ] {
+            if let Some(gradient) = gradient {
+                for (handle, point) in gradient.handles() {
+                    points.insert(format!("{}{}", prefix, handle), point);
+                }
+            }
+        }
+        points
+    }
+
+    fn is_gradient_handle(&self, index: &str) -> bool {
+        index.starts_with(Curve::STROKE_GRADIENT) || index.starts_with(Curve::FILL_GRADIENT)
+    }
+
+    fn move_gradient_handle(&mut self, index: &str, point: Point) {
+        if let Some(handle) = index.strip_prefix(Curve::STROKE_GRADIENT) {
+            if let Some(gradient) = &mut self.stroke_gradient {
+                gradient.move_handle(handle, point);
+            }
+        } else if let Some(handle) = index.strip_prefix(Curve::FILL_GRADIENT) {
+            if let Some(gradient) = &mut self.fill_gradient {
+                gradient.move_handle(handle, point);
+            }
+        }
+    }
+
+    //用来计算整体移动了多少的参考点，取名字最小的那个保证前后是同一个点
+    fn anchor(&self) -> Option<(String, Point)> {
+        self.shape
+            .points()
+            .into_iter()
+            .min_by(|(a, _), (b, _)| a.cmp(b))
+    }
+
+    //整体平移，渐变跟着图形一起移动
+    pub fn translate(&mut self, offset: Vector) {
+        if let Some((key, point)) = self.anchor() {
+            let point = self.transform.apply(point);
+            self.update(CurveMessage::Shape(ShapeMessage::Move(key, point + offset)));
+        }
+    }
+
+    //路径和所有点的包围盒，再加上一半线宽
+    pub fn bounds(&self) -> Option<Rectangle> {
+        let points = self.points();
+        let path = self
+            .outline()
+            .and_then(|data| path_bounds(&self.transform.apply_data(&data)))
+            .map(|(min, max)| [min, max]);
+        let min = points
+            .values()
+            .chain(path.iter().flatten())
+            .copied()
+            .reduce(|a, b| Point::new(a.x.min(b.x), a.y.min(b.y)))?;
+        let max = points
+            .values()
+            .chain(path.iter().flatten())
+            .copied()
+            .reduce(|a, b| Point::new(a.x.max(b.x), a.y.max(b.y)))?;
+
+        let half = (self.canvas_width() / 2.0).max(0.5);
+        Some(Rectangle::new(
+            Point::new(min.x - half, min.y - half),
+            Size::new(max.x - min.x + half * 2.0, max.y - min.y + half * 2.0),
+        ))
+    }
+
+    //吸附用的点：图形的点、直线段的中点和包围盒的中心
+    pub fn snap_points(&self) -> Vec<Point> {
+        let mut points = self
+            .shape
+            .points()
+            .into_values()
+            .map(|point| self.transform.apply(point))
+            .collect::<Vec<_>>();
+        if let Some(data) = self.shape.export_as_svg() {
+            points.extend(segment_midpoints(&self.transform.apply_data(&data)));
+        }
+        points.extend(self.bounds().map(|bounds| bounds.center()));
+        points
+    }
+
+    //新的渐变铺满图形所有点的包围盒
+    fn new_gradient(&self, paint: Paint, color: Color) -> Option<Gradient> {
+        let points = self.shape.points();
+        let min = points
+            .values()
+            .copied()
+            .reduce(|a, b| Point::new(a.x.min(b.x), a.y.min(b.y)))
+            .unwrap_or(Point::ORIGIN);
+        let max = points
+            .values()
+            .copied()
+            .reduce(|a, b| Point::new(a.x.max(b.x), a.y.max(b.y)))
+            .unwrap_or(Point::new(100.0, 100.0));
+        Gradient::new(paint, color, min, max)
+    }
+
+    //画布不支持渐变，用平均颜色代替
+    fn stroke_color(&self) -> Color {
+        self.stroke_gradient
+            .as_ref()
+            .map(Gradient::average)
+            .unwrap_or(self.color)
+    }
+
+    fn fill_color(&self) -> Option<Color> {
+        self.fill_gradient
+            .as_ref()
+            .map(Gradient::average)
+            .or(self.fill)
+    }
+
     #[inline(always)]
-    pub fn preview(&self, frame: &mut Frame, cursor_position: Point) {
+    pub fn preview(&self, frame: &mut Frame, cursor_position: Point, zoom: f32) {
         if let Some(path) = self.shape.preview(cursor_position) {
             frame.stroke(
                 &path,
                 Stroke {
                     color: self.color,
-                    width: self.width,
+                    width: self.width * zoom,
                     line_cap: self.line_cap.into(),
                     line_join: self.line_join.into(),
-                    line_dash: LineDash::default(),
+                    line_dash: self.line_dash(),
                 },
             );
         }
     }
 
     #[inline(always)]
-    pub fn draw(&self, frame: &mut Frame, selected: bool) {
-        if let (Some(path), selected) = self.shape.draw(selected) {
-            frame.stroke(
-                &path,
-                Stroke {
-                    color: self.color,
-                    width: self.width,
-                    line_cap: self.line_cap.into(),
-                    line_join: self.line_join.into(),
-                    line_dash: LineDash::default(),
+    //画布缩放时路径跟着缩放，线宽和文字大小要自己乘上缩放，控制点保持原来的大小
+    pub fn draw(&self, frame: &mut Frame, selected: bool, zoom: f32) {
+        let show_handles = selected;
+        let (path, selected) = self.geometry(selected, zoom);
+        //画布上没有图层，整体透明度只能乘到每种颜色上，重叠的部分会比导出的深一些
+        let color = faded(self.stroke_color(), self.opacity);
+        let fill = self.fill_color().map(|fill| faded(fill, self.opacity));
+
+        if let Some(shadow) = &self.shadow {
+            self.draw_shadow(frame, &path, shadow, zoom);
+        }
+
+        match self.paint_order {
+            PaintOrder::Normal => {
+                self.draw_fill(frame, &path, fill);
+                self.draw_stroke(frame, &path, color, zoom);
+                self.draw_markers(frame, color, zoom);
+            }
+            PaintOrder::Stroke => {
+                self.draw_stroke(frame, &path, color, zoom);
+                self.draw_fill(frame, &path, fill);
+                self.draw_markers(frame, color, zoom);
+            }
+            PaintOrder::Markers => {
+                self.draw_markers(frame, color, zoom);
+                self.draw_fill(frame, &path, fill);
+                self.draw_stroke(frame, &path, color, zoom);
+            }
+        }
+
+        //文字用描边的颜色填充
+        if let Some(text) = self.text(zoom) {
+            frame.fill_text(CanvasText { color, ..text });
+            //画布上的字不能旋转和斜切，画出变换之后的文字框，和导出的方向一致
+            if !self.transform.is_identity() {
+                if let Some(outline) = self.outline() {
+                    frame.stroke(
+                        &data_to_path(&self.transform.apply_data(&outline)),
+                        Stroke {
+                            width: 1.0,
+                            color,
+                            ..Stroke::default()
+                        },
+                    );
+                }
+            }
+        }
+
+        if let Some(selection_highlight) = selected {
+            frame.fill(&selection_highlight, Fill { ..Fill::default() });
+        }
+
+        //选中时显示渐变的控制点，连线表示渐变的方向或半径
+        if show_handles {
+            for gradient in [&self.stroke_gradient, &self.fill_gradient]
+                .into_iter()
+                .flatten()
+            {
+                let handles = gradient
+                    .handles()
+                    .into_iter()
+                    .map(|(handle, point)| (handle, self.transform.apply(point)))
+                    .collect::<Vec<_>>();
+                frame.stroke(
+                    &Path::new(|builder| {
+                        if let [(_, from), (_, to), ..] = handles[..] {
+                            builder.move_to(from);
+                            builder.line_to(to);
+                        }
+                    }),
+                    Stroke {
+                        width: 1.0,
+                        ..Stroke::default()
+                    },
+                );
+                frame.stroke(
+                    &Path::new(|builder| {
+                        for (_, point) in handles.iter() {
+                            builder.circle(*point, Curve::HANDLE_RADIUS / zoom);
+                        }
+                    }),
+                    Stroke::default(),
+                );
+            }
+        }
+    }
+
+    //图形的路径，文字没有路径，用估算的文字框代替
+    fn outline(&self) -> Option<Data> {
+        match &self.shape {
+            ShapeEnum::Text(text) => text.outline(),
+            shape => shape.export_as_svg(),
+        }
+    }
+
+    //有变换矩阵时画变换之后的路径，选中时在变换之后的点上画圆圈
+    fn geometry(&self, selected: bool, zoom: f32) -> (Option<Path>, Option<Path>) {
+        let path = if self.transform.is_identity() {
+            self.shape.draw(selected).0
+        } else {
+            self.shape
+                .export_as_svg()
+                .map(|data| data_to_path(&self.transform.apply_data(&data)))
+        };
+        let highlight = selected.then(|| {
+            let points = self.shape.points();
+            Path::new(|builder| {
+                for point in points.values() {
+                    builder.circle(self.transform.apply(*point), Curve::HANDLE_RADIUS / zoom);
+                }
+            })
+        });
+        (path, highlight)
+    }
+
+    //画布上的文字没法旋转和斜切，只移动位置
+    fn text(&self, zoom: f32) -> Option<CanvasText> {
+        self.shape.text().map(|text| CanvasText {
+            position: self.transform.apply(text.position),
+            size: text.size * self.transform.scale_factor() * zoom,
+            ..text
+        })
+    }
+
+    //线宽跟着变换缩放，导出时不缩放线宽的曲线除外
+    fn canvas_width(&self) -> f32 {
+        if self.non_scaling_stroke {
+            self.width
+        } else {
+            self.width * self.transform.scale_factor()
+        }
+    }
+
+    fn draw_fill(&self, frame: &mut Frame, path: &Option<Path>, fill: Option<Color>) {
+        if let (Some(path), Some(fill)) = (path, fill) {
+            frame.fill(
+                path,
+                Fill {
+                    color: fill,
+                    rule: self.fill_rule.into(),
                 },
             );
+        }
+    }
+
+    fn draw_stroke(&self, frame: &mut Frame, path: &Option<Path>, color: Color, zoom: f32) {
+        if let Some(path) = path {
+            frame.stroke(path, self.stroke(color, self.canvas_line_join(), zoom));
+            self.draw_miters(frame, color, self.canvas_width() / 2.0);
+        }
+    }
+
+    //箭头的大小跟随线宽，箭头很小，直接用画布自己的斜接
+    fn draw_markers(&self, frame: &mut Frame, color: Color, zoom: f32) {
+        let line_join = self.line_join.into();
+        if let ShapeEnum::Arrow(arrow) = &self.shape {
+            for (marker, path) in arrow.markers(self.width) {
+                if marker.is_filled() {
+                    frame.fill(
+                        &path,
+                        Fill {
+                            color,
+                            ..Fill::default()
+                        },
+                    );
+                } else {
+                    frame.stroke(&path, self.stroke(color, line_join, zoom));
+                }
+            }
+        }
+    }
+
+    //阴影画在图形下面，模糊用几层逐渐变宽、每层都很淡的描边叠出来
+    fn draw_shadow(&self, frame: &mut Frame, path: &Option<Path>, shadow: &Shadow, zoom: f32) {
+        let color = faded(shadow.color, self.opacity);
+        let layers = if shadow.blur > 0.0 { Shadow::LAYERS } else { 0 };
+        let layer_color = faded(color, 1.0 / (layers + 1) as f32);
+
+        frame.with_save(|frame| {
+            frame.translate(shadow.offset());
+
+            if let Some(path) = path {
+                if self.fill.is_some() {
+                    frame.fill(
+                        path,
+                        Fill {
+                            color,
+                            rule: self.fill_rule.into(),
+                        },
+                    );
+                }
+                for layer in 0..=layers {
+                    let spread = shadow.blur * layer as f32 / Shadow::LAYERS as f32;
+                    frame.stroke(
+                        path,
+                        Stroke {
+                            width: (self.canvas_width() + spread * 2.0) * zoom,
+                            ..self.stroke(layer_color, self.canvas_line_join(), zoom)
+                        },
+                    );
+                    self.draw_miters(frame, layer_color, self.canvas_width() / 2.0 + spread);
+                }
+            }
+            self.draw_markers(frame, color, zoom);
 
-            if let Some(selection_highlight) = selected {
-                frame.fill(&selection_highlight, Fill { ..Fill::default() });
+            if let Some(text) = self.text(zoom) {
+                frame.fill_text(CanvasText { color, ..text });
             }
+        });
+    }
+
+    fn stroke(&self, color: Color, line_join: LineJoin, zoom: f32) -> Stroke<'_> {
+        Stroke {
+            color,
+            width: self.canvas_width() * zoom,
+            line_cap: self.line_cap.into(),
+            line_join,
+            line_dash: self.line_dash(),
         }
     }
 
+    //画布的斜接限制固定为4，实线先用斜角连接，再由draw_miters补上没超过限制的尖角
+    //虚线的拐角可能落在间隔里，只能用画布自己的斜接，和导出的结果不完全一样
+    fn canvas_line_join(&self) -> LineJoin {
+        match self.line_join {
+            EqLineJoin::Miter if self.segments.is_empty() => LineJoin::Bevel,
+            line_join => line_join.into(),
+        }
+    }
+
+    //half_width是文档里的半个线宽
+    fn draw_miters(&self, frame: &mut Frame, color: Color, half_width: f32) {
+        if self.line_join != EqLineJoin::Miter || !self.segments.is_empty() {
+            return;
+        }
+        let joins = self.cached_joins();
+        let wedges = joins
+            .iter()
+            .filter(|join| join.ratio() <= self.miter_limit)
+            .filter_map(|join| join.wedge(half_width))
+            .collect::<Vec<_>>();
+        if wedges.is_empty() {
+            return;
+        }
+        frame.fill(
+            &Path::new(|builder| {
+                //斜角连接已经画到了a和b之间的直线，只补外面的三角形，半透明时不会重叠
+                for [a, tip, b] in &wedges {
+                    builder.move_to(*a);
+                    builder.line_to(*tip);
+                    builder.line_to(*b);
+                    builder.close();
+                }
+            }),
+            Fill {
+                color,
+                ..Fill::default()
+            },
+        );
+    }
+
+    //变换之后的路径上的拐角，算过一次之后缓存起来
+    fn cached_joins(&self) -> Ref<'_, [MiterJoin]> {
+        if self.miter_joins.borrow().is_none() {
+            let joins = self
+                .shape
+                .export_as_svg()
+                .map(|data| miter_joins(&self.transform.apply_data(&data)))
+                .unwrap_or_default();
+            *self.miter_joins.borrow_mut() = Some(joins);
+        }
+        Ref::map(self.miter_joins.borrow(), |joins| {
+            joins.as_deref().unwrap_or_default()
+        })
+    }
+
+    fn line_dash(&self) -> LineDash<'_> {
+        LineDash {
+            segments: &self.segments,
+            offset: self.canvas_dash_offset(),
+        }
+    }
+
+    //画布上的虚线只能从某一段线段的开头开始，offset取最近的线段开头，和导出的会差不到半个周期
+    fn canvas_dash_offset(&self) -> usize {
+        let mut pattern = self.segments.clone();
+        if pattern.len() % 2 == 1 {
+            pattern.extend_from_slice(&self.segments);
+        }
+        let period = pattern.iter().sum::<f32>();
+        if period <= 0.0 {
+            return 0;
+        }
+
+        let offset = self.offset.rem_euclid(period);
+        let mut start = 0.0;
+        let mut nearest = (0, offset.min(period - offset));
+        for (index, pair) in pattern.chunks_exact(2).enumerate() {
+            let distance = (offset - start).abs();
+            if distance < nearest.1 {
+                nearest = (index * 2, distance);
+            }
+            start += pair[0] + pair[1];
+        }
+        nearest.0
+    }
+
+    pub(crate) fn export_dash(&self) -> Option<(String, f32)> {
+        if self.segments.is_empty() {
+            return None;
+        }
+
+        let array = self
+            .segments
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Some((array, self.offset))
+    }
+
+    //需要放进<defs>里的元素，id用来区分不同的曲线
+    pub fn defs(&self, id: usize) -> Vec<Element> {
+        let mut defs: Vec<Element> = match &self.shape {
+            ShapeEnum::Arrow(arrow) => {
+                let color = self.stroke_color();
+                let fill = get_format_rgb(color);
+                [
+                    arrow
+                        .start
+                        .export_as_svg(&marker_id(id, "start"), &fill, color.a),
+                    arrow
+                        .end
+                        .export_as_svg(&marker_id(id, "end"), &fill, color.a),
+                ]
+                .into_iter()
+                .flatten()
+                .map(Into::into)
+                .collect()
+            }
+            _ => vec![],
+        };
+
+        if let Some(gradient) = &self.stroke_gradient {
+            defs.push(gradient.export_as_svg(&gradient_id(id, "stroke")));
+        }
+        if let Some(gradient) = &self.fill_gradient {
+            defs.push(gradient.export_as_svg(&gradient_id(id, "fill")));
+        }
+        if let Some(shadow) = &self.shadow {
+            defs.push(shadow.export_as_svg(&shadow_id(id)).into());
+        }
+
+        defs
+    }
+
     #[inline(always)]
-    pub fn save(&self) -> Option<SvgPath> {
+    pub fn save(&self, id: usize) -> Option<Element> {
+        let stroke = match &self.stroke_gradient {
+            Some(_) => format!("url(#{})", gradient_id(id, "stroke")),
+            None => get_format_rgb(self.color),
+        };
+
+        //颜色里不带透明度，透明度单独导出
+        let opacity = match &self.stroke_gradient {
+            Some(_) => 1.0,
+            None => self.color.a,
+        };
+
+        if let ShapeEnum::Text(text) = &self.shape {
+            return text.export_as_svg_text().map(|text| {
+                self.effects(
+                    id,
+                    text.set("fill", stroke).set("fill-opacity", opacity).into(),
+                )
+            });
+        }
+
         //小写大写貌似不区分
         let data = self.shape.export_as_svg()?;
-        Some(
-            SvgPath::new()
-                .set("fill", "none")
-                .set("stroke", get_format_color(self.color))
-                .set("stroke-width", self.width)
-                .set("stroke-linecap", self.line_cap.to_string())
-                .set("stroke-linejoin", self.line_join.to_string())
-                .set("d", data),
-        )
+        let mut path = match (self.fill, &self.fill_gradient) {
+            (Some(_), Some(_)) => SvgPath::new()
+                .set("fill", format!("url(#{})", gradient_id(id, "fill")))
+                .set("fill-rule", self.fill_rule.to_string()),
+            (Some(fill), None) => SvgPath::new()
+                .set("fill", get_format_rgb(fill))
+                .set("fill-opacity", fill.a)
+                .set("fill-rule", self.fill_rule.to_string()),
+            (None, _) => SvgPath::new().set("fill", "none"),
+        }
+        .set("stroke", stroke)
+        .set("stroke-opacity", opacity)
+        .set("stroke-width", self.width)
+        .set("stroke-linecap", self.line_cap.to_string())
+        .set("stroke-linejoin", self.line_join.to_string())
+        .set("d", data);
+
+        if let Some((array, offset)) = self.export_dash() {
+            path = path
+                .set("stroke-dasharray", array)
+                .set("stroke-dashoffset", offset);
+        }
+
+        if let Some(style) = &self.style {
+            path = path.set("class", class_name(style));
+        }
+
+        if self.line_join == EqLineJoin::Miter {
+            path = path.set("stroke-miterlimit", self.miter_limit);
+        }
+        if self.paint_order != PaintOrder::Normal {
+            path = path.set("paint-order", self.paint_order.to_string());
+        }
+        if self.non_scaling_stroke {
+            path = path.set("vector-effect", "non-scaling-stroke");
+        }
+
+        if let ShapeEnum::Arrow(arrow) = &self.shape {
+            if arrow.start != Marker::None {
+                path = path.set("marker-start", format!("url(#{})", marker_id(id, "start")));
+            }
+            if arrow.end != Marker::None {
+                path = path.set("marker-end", format!("url(#{})", marker_id(id, "end")));
+            }
+        }
+
+        Some(self.effects(id, path.into()))
+    }
+
+    //对象整体的变换、透明度、混合模式和阴影
+    fn effects(&self, id: usize, mut element: Element) -> Element {
+        if !self.transform.is_identity() {
+            element.assign("transform", self.transform.export());
+        }
+        if self.opacity < 1.0 {
+            element.assign("opacity", self.opacity);
+        }
+        if self.blend_mode != BlendMode::Normal {
+            element.assign("style", format!("mix-blend-mode: {}", self.blend_mode));
+        }
+        if self.shadow.is_some() {
+            element.assign("filter", format!("url(#{})", shadow_id(id)));
+        }
+        element
+    }
+}
+
+fn shadow_id(id: usize) -> String {
+    format!("curve-{}-shadow", id)
+}
+
+fn faded(color: Color, opacity: f32) -> Color {
+    Color {
+        a: color.a * opacity,
+        ..color
+    }
+}
+
+fn marker_id(id: usize, end: &str) -> String {
+    format!("curve-{}-marker-{}", id, end)
+}
+
+fn gradient_id(id: usize, paint: &str) -> String {
+    format!("curve-{}-{}-gradient", id, paint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dashed(segments: &[f32], offset: f32) -> Curve {
+        Curve {
+            segments: segments.to_vec(),
+            offset,
+            ..Curve::default()
+        }
+    }
+
+    #[test]
+    fn offset_is_exported_as_a_length() {
+        let curve = dashed(&[4.0, 2.0], -2.5);
+        assert_eq!(curve.export_dash(), Some((String::from("4 2"), -2.5)));
+        assert_eq!(dashed(&[], 3.0).export_dash(), None);
+    }
+
+    #[test]
+    fn canvas_offset_is_the_nearest_dash() {
+        //一个周期是4 2 1 3，两段线段从0和6开始
+        let pattern = [4.0, 2.0, 1.0, 3.0];
+        assert_eq!(dashed(&pattern, 0.0).canvas_dash_offset(), 0);
+        assert_eq!(dashed(&pattern, 5.5).canvas_dash_offset(), 2);
+        assert_eq!(dashed(&pattern, 9.0).canvas_dash_offset(), 0);
+        assert_eq!(dashed(&pattern, -4.5).canvas_dash_offset(), 2);
+        //奇数段的虚线重复一次才是一个周期
+        assert_eq!(dashed(&[3.0], 3.2).canvas_dash_offset(), 0);
+        assert_eq!(dashed(&[3.0, 1.0, 2.0], 7.0).canvas_dash_offset(), 4);
     }
 }
@@ -1,27 +1,51 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use iced::keyboard::{KeyCode, Modifiers};
 use iced::pure::widget::{
     canvas::Canvas as IcedCanvas,
-    canvas::{event, Cache, Cursor, Event, Frame, Geometry, Path, Program, Stroke},
-    text_input, Column, PickList, Row, Slider, Space, Text,
+    canvas::{
+        event, Cache, Cursor, Event, Frame, Geometry, Path, Program, Stroke, Text as CanvasText,
+    },
+    text_input, Button, Checkbox, Column, PickList, Row, Slider, Space, Text,
 };
 use iced::pure::Element;
-use iced::{keyboard, mouse, Alignment, Length, Point, Rectangle as IcedRectangle};
+use iced::{
+    alignment::Vertical, keyboard, mouse, Alignment, Color, Length, Point,
+    Rectangle as IcedRectangle,
+};
 
-use svg::Document;
+use svg::{
+    node::element::{Definitions, Style},
+    Document,
+};
 
 use super::{
     curve::*,
-    shape::{Shape, ShapeEnum, ShapeMessage},
+    effect::{BlendMode, Shadow, ShadowMessage},
+    gradient::{Gradient, GradientMessage, Paint},
+    group::Group,
+    history::{EditCommand, History, MergeKey, Snapshot},
+    layer::{Layer, LayerMessage},
+    named_style::NamedStyle,
+    shape::{
+        Arc, ArcMode, Arrow, Freehand, Marker, Shape, ShapeEnum, ShapeMessage, Text as TextShape,
+        TextAlign, TextFont,
+    },
+    snap::{Guide, Snap, SnapMessage},
     style,
+    transform::{Gizmo, Handle, Transform, TransformInput, TransformMessage},
+    utils::{format_attribute, from_hsl, from_hsv, get_format_color, parse_color, to_hsl, to_hsv},
+    viewport::{ViewMessage, Viewport},
 };
 use crate::io::dialogs::save as save_file;
 
 #[derive(Debug, Clone)]
 pub enum EditMessage {
     AddWithClick(Point),
+    AddWithDrag(Point, Point),
     AddFromPending,
     Curve(CurveMessage),
     ChangeShape(ShapeEnum),
@@ -29,77 +53,455 @@ pub enum EditMessage {
     CurvePasted(Point),
     Clear,
     RemoveCurve,
+    //把选中的曲线编成一个组，或者解散选中的组
+    Group,
+    Ungroup,
+    Layer(LayerMessage),
+    //调整叠放顺序，拖动列表时从一个位置挪到另一个位置
+    Restack(ZOrder),
+    Reordered(usize, usize),
+    //旋转、缩放、斜切和翻转选中的曲线
+    Transform(TransformMessage),
+    //缩放和平移画布，不修改文档
+    View(ViewMessage),
+    //网格和吸附的设置
+    Snap(SnapMessage),
+    //吸管从画布或者图片上取到的颜色
+    ColorPicked(Color),
+    //共用样式
+    StyleCreated,
+    StyleApplied(String),
+    StyleDetached,
+    StyleEdited(Option<usize>),
+    StyleRenamed(String),
+    StyleRemoved,
+    //撤销和重做
+    Undo,
+    Redo,
+    //输入的深度按回车之后才生效，免得删改数字时先截断了记录
+    HistoryDepth(String),
+    HistoryDepthSubmitted,
+    //在画布上松开鼠标，结束这次拖动的合并
+    Released,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZOrder {
+    Forward,
+    Backward,
+    Front,
+    Back,
+}
+
+#[derive(Debug)]
 pub struct Edit {
     pub curves: Vec<Rc<RefCell<Curve>>>,
     pending: Rc<RefCell<Curve>>,
     pub dirty: bool,
 
-    pub copied_curve: Option<Rc<RefCell<Curve>>>,
-    selected: Rc<RefCell<(Option<usize>, Option<String>)>>,
+    pub copied_curves: Vec<Rc<RefCell<Curve>>>,
+    selected: Rc<RefCell<Selection>>,
     cache: Rc<RefCell<Cache>>,
+
+    color_space: ColorSpace,
+    //吸管打开时记录取到的颜色用在哪里
+    eyedropper: Option<ColorTarget>,
+    //最近选定或者输入的颜色，最新的排在前面
+    recent_colors: Vec<Color>,
+    //正在连续修改颜色的目标，拖动滑条或者逐字输入时只更新最新的一个
+    recoloring: Option<ColorTarget>,
+
+    pub styles: Vec<NamedStyle>,
+    //正在编辑的样式，编辑时修改的是style_template
+    editing_style: Option<usize>,
+    style_template: Rc<RefCell<Curve>>,
+    //正在输入的样式名，重名时先不生效
+    style_name_input: Option<String>,
+
+    history: History,
+    //正在输入的撤销深度
+    depth_input: Option<String>,
+
+    //至少有一个图层
+    pub layers: Vec<Layer>,
+    active_layer: usize,
+
+    transform_input: TransformInput,
+    viewport: Rc<RefCell<Viewport>>,
+    snap: Snap,
+}
+
+impl Default for Edit {
+    fn default() -> Self {
+        Edit::new(vec![], vec![], vec![])
+    }
 }
 
 impl Edit {
-    pub fn new(curves: Vec<Rc<RefCell<Curve>>>) -> Self {
+    const PALETTE_SIZE: usize = 10;
+
+    pub fn new(
+        curves: Vec<Rc<RefCell<Curve>>>,
+        styles: Vec<NamedStyle>,
+        layers: Vec<Layer>,
+    ) -> Self {
         Edit {
             curves,
-            ..Edit::default()
+            pending: Rc::default(),
+            dirty: false,
+            copied_curves: vec![],
+            selected: Rc::default(),
+            cache: Rc::default(),
+            color_space: ColorSpace::default(),
+            eyedropper: None,
+            recent_colors: vec![],
+            recoloring: None,
+            styles,
+            editing_style: None,
+            style_template: Rc::default(),
+            style_name_input: None,
+            history: History::default(),
+            depth_input: None,
+            layers: if layers.is_empty() {
+                vec![Layer::default()]
+            } else {
+                layers
+            },
+            active_layer: 0,
+            transform_input: TransformInput::default(),
+            viewport: Rc::default(),
+            snap: Snap::default(),
         }
     }
 
     pub fn update(&mut self, message: EditMessage) {
+        self.remember_color(&message);
         match message {
             EditMessage::Curve(cm) => {
-                if let CurveMessage::CurveSelected(index) = cm {
-                    self.selected.borrow_mut().0 = Some(index);
-                } else {
-                    if let Some(index) = self.selected.borrow().0 {
-                        self.curves[index].borrow_mut().update(cm);
-                    } else {
-                        self.pending.borrow_mut().update(cm)
+                //多选时拖动单个点只作用在主要的曲线上，其余修改作用在所有选中的曲线上
+                let grouped = self.selected.borrow().len() > 1
+                    && !matches!(&cm, CurveMessage::Shape(sm) if !matches!(sm, ShapeMessage::Move(..)));
+
+                if let CurveMessage::Released = cm {
+                    self.history.close_merge();
+                } else if let CurveMessage::CurveSelected(index) = cm {
+                    //隐藏和锁定的图层上的曲线不能选中，组整体选中
+                    if self
+                        .curves
+                        .get(index)
+                        .is_some_and(|curve| is_selectable(&self.layers, &curve.borrow()))
+                    {
+                        let mut selected = self.selected.borrow_mut();
+                        selected.single(index, None);
+                        selected.extend(members(&self.curves, index));
                     }
+                    self.editing_style = None;
+                } else if let CurveMessage::EyedropperToggled(target) = cm {
+                    self.eyedropper = match self.eyedropper {
+                        Some(t) if t == target => None,
+                        _ => Some(target),
+                    };
+                } else if let CurveMessage::ColorSpaceSelected(space) = cm {
+                    self.color_space = space;
+                } else if let Some(style) = self.editing_style {
+                    let key = MergeKey::new(None, &cm);
+                    let name = self.styles[style].name.clone();
+                    let indices = (0..self.curves.len())
+                        .filter(|index| self.curves[*index].borrow().style.as_ref() == Some(&name))
+                        .collect::<Vec<_>>();
+                    let (before, style_before) =
+                        (self.curves_at(&indices), self.styles[style].clone());
+                    self.style_template.borrow_mut().update(cm);
+                    self.sync_style(style);
+                    let mut commands = self.modified(&indices, before);
+                    commands.push(EditCommand::Style {
+                        index: style,
+                        before: style_before,
+                        after: self.styles[style].clone(),
+                    });
+                    self.history.push(EditCommand::Batch(commands), key);
+                } else if grouped {
+                    let key = MergeKey::new(None, &cm);
+                    let indices = self.selected.borrow().curves.clone();
+                    let before = self.curves_at(&indices);
+                    self.update_selection(cm);
+                    self.record_modified(&indices, before, key);
+                } else if let Some(index) = self.selected.borrow().primary() {
+                    let before = Box::new(self.curves[index].borrow().clone());
+                    let key = MergeKey::new(Some(index), &cm);
+                    self.curves[index].borrow_mut().update(cm);
+                    let after = Box::new(self.curves[index].borrow().clone());
+                    self.history.push(
+                        EditCommand::Modify {
+                            index,
+                            before,
+                            after,
+                        },
+                        key,
+                    );
+                } else {
+                    self.pending.borrow_mut().update(cm);
                 }
             }
             EditMessage::AddWithClick(cursor_position) => {
-                *self.selected.borrow_mut() = (None, None);
+                self.history.close_merge();
+                self.selected.borrow_mut().clear();
                 self.pending
                     .borrow_mut()
                     .shape
                     .update(ShapeMessage::Labor(cursor_position));
             }
+            EditMessage::AddWithDrag(from, to) => {
+                self.selected.borrow_mut().clear();
+                self.pending
+                    .borrow_mut()
+                    .shape
+                    .update(ShapeMessage::LaborDrag(from, to));
+            }
             EditMessage::ChangeShape(s) => {
                 self.pending.borrow_mut().shape = s;
             }
             EditMessage::Clear => {
+                let before = self.snapshot();
                 self.curves.clear();
-                *self.selected.borrow_mut() = (None, None);
+                self.selected.borrow_mut().clear();
+                self.record_snapshot(before, None);
             }
             EditMessage::RemoveCurve => {
-                if let (Some(index), _) = self.selected.replace((None, None)) {
-                    self.curves.remove(index);
+                let selected = self.selected.replace(Selection::default()).sorted();
+                if let [index] = selected[..] {
+                    let curve = self.curves.remove(index).borrow().clone();
+                    self.history
+                        .push(EditCommand::Remove { index, curve }, None);
+                } else if !selected.is_empty() {
+                    let before = self.snapshot();
+                    //从后往前删，前面的下标不会变
+                    for index in selected.into_iter().rev() {
+                        self.curves.remove(index);
+                    }
+                    self.record_snapshot(before, None);
                 }
             }
             EditMessage::AddFromPending => {
-                self.curves
-                    .push(Rc::new(Rc::make_mut(&mut self.pending).to_owned()));
-                self.pending.borrow_mut().shape.update(ShapeMessage::Reset);
-                *self.selected.borrow_mut() = (Some(self.curves.len() - 1), None);
+                //点数不定的图形需要先结束绘制，点数固定的图形这一步不会有影响
+                self.pending.borrow_mut().shape.update(ShapeMessage::Finish);
+                if self.pending.borrow().shape.is_complete() {
+                    self.pending.borrow_mut().layer = self.active_layer;
+                    self.curves
+                        .push(Rc::new(Rc::make_mut(&mut self.pending).to_owned()));
+                    self.pending.borrow_mut().shape.update(ShapeMessage::Reset);
+                    self.selected
+                        .borrow_mut()
+                        .single(self.curves.len() - 1, None);
+                    self.record_insert(self.curves.len() - 1);
+                }
+            }
+            EditMessage::Group => {
+                let selected = self.selected.borrow().sorted();
+                if let Some(&last) = selected.last() {
+                    let before = self.snapshot();
+                    let id = self.next_group_id();
+                    //组里的曲线放到同一个图层
+                    let layer = self.curves[last].borrow().layer;
+                    //组里的曲线挪到一起，放在最上面那条曲线的位置
+                    let mut members = selected
+                        .iter()
+                        .rev()
+                        .map(|index| self.curves.remove(*index))
+                        .collect::<Vec<_>>();
+                    members.reverse();
+                    let start = last + 1 - members.len();
+                    for (offset, curve) in members.into_iter().enumerate() {
+                        curve.borrow_mut().group.wrap(id);
+                        curve.borrow_mut().layer = layer;
+                        self.curves.insert(start + offset, curve);
+                    }
+                    *self.selected.borrow_mut() = Selection {
+                        curves: (start..start + selected.len()).collect(),
+                        point: None,
+                    };
+                    self.record_snapshot(before, None);
+                }
+            }
+            EditMessage::Ungroup => {
+                let before = self.snapshot();
+                let mut dissolved = false;
+                for index in self.selected.borrow().curves.iter() {
+                    dissolved |= self.curves[*index].borrow_mut().group.dissolve();
+                }
+                if dissolved {
+                    self.record_snapshot(before, None);
+                }
+            }
+            EditMessage::Layer(lm) => self.update_layer(lm),
+            //视图和吸附不属于文档，不需要置脏位
+            EditMessage::View(vm) => {
+                let content = self
+                    .curves
+                    .iter()
+                    .filter_map(|curve| curve.borrow().bounds())
+                    .reduce(union);
+                self.viewport.borrow_mut().update(vm, content);
+                self.redraw();
+                return;
             }
+            EditMessage::Snap(sm) => {
+                self.snap.update(sm);
+                self.redraw();
+                return;
+            }
+            EditMessage::Transform(tm) => self.update_transform(tm),
+            EditMessage::Restack(order) => self.restack(order),
+            EditMessage::Reordered(from, to) => self.reorder(from, to),
             EditMessage::CurveCopied => {
-                if let Some(index) = self.selected.borrow().0 {
-                    self.copied_curve = Some(self.curves[index].clone());
+                let selected = self.selected.borrow().sorted();
+                if !selected.is_empty() {
+                    self.copied_curves = selected
+                        .into_iter()
+                        .map(|index| self.curves[index].clone())
+                        .collect();
+                }
+            }
+            EditMessage::ColorPicked(color) => {
+                if let Some(target) = self.eyedropper.take() {
+                    self.update(EditMessage::Curve(CurveMessage::SwatchPicked(
+                        target, color,
+                    )));
+                }
+            }
+            EditMessage::StyleCreated => {
+                let before = self.snapshot();
+                let mut n = self.styles.len() + 1;
+                while self.styles.iter().any(|s| s.name == format!("style-{}", n)) {
+                    n += 1;
+                }
+                let style =
+                    NamedStyle::from_curve(format!("style-{}", n), &self.current().borrow());
+                for curve in self.targets() {
+                    style.apply(&mut curve.borrow_mut());
+                }
+                self.styles.push(style);
+                self.record_snapshot(before, None);
+                self.update(EditMessage::StyleEdited(Some(self.styles.len() - 1)));
+            }
+            EditMessage::StyleApplied(name) => {
+                let before = self.snapshot();
+                if let Some(style) = self.styles.iter().find(|s| s.name == name) {
+                    for curve in self.targets() {
+                        style.apply(&mut curve.borrow_mut());
+                    }
+                }
+                //待添加的曲线不在文档里，不需要记录
+                if !self.selected.borrow().is_empty() {
+                    self.record_snapshot(before, None);
+                }
+            }
+            EditMessage::StyleDetached => {
+                let before = self.snapshot();
+                for curve in self.targets() {
+                    curve.borrow_mut().style = None;
+                }
+                if !self.selected.borrow().is_empty() {
+                    self.record_snapshot(before, None);
+                }
+            }
+            EditMessage::StyleEdited(index) => {
+                self.editing_style = index.filter(|i| *i < self.styles.len());
+                if let Some(index) = self.editing_style {
+                    *self.style_template.borrow_mut() = self.styles[index].template();
+                }
+                self.style_name_input = None;
+            }
+            EditMessage::StyleRenamed(name) => {
+                if let Some(index) = self.editing_style {
+                    let before = self.snapshot();
+                    let valid = !name.trim().is_empty()
+                        && !self
+                            .styles
+                            .iter()
+                            .enumerate()
+                            .any(|(i, s)| i != index && s.name == name);
+                    if valid {
+                        let old = std::mem::replace(&mut self.styles[index].name, name.clone());
+                        for curve in self.curves.iter().chain([&self.pending]) {
+                            let mut curve = curve.borrow_mut();
+                            if curve.style.as_ref() == Some(&old) {
+                                curve.style = Some(name.clone());
+                            }
+                        }
+                        //输入名字时每个字符都会发消息，合并成一次
+                        self.record_snapshot(before, Some(MergeKey::style_name()));
+                    }
+                    self.style_name_input = Some(name);
+                }
+            }
+            EditMessage::StyleRemoved => {
+                if let Some(index) = self.editing_style.take() {
+                    let before = self.snapshot();
+                    let removed = self.styles.remove(index);
+                    for curve in self.curves.iter().chain([&self.pending]) {
+                        let mut curve = curve.borrow_mut();
+                        if curve.style.as_ref() == Some(&removed.name) {
+                            curve.style = None;
+                        }
+                    }
+                    self.record_snapshot(before, None);
                 }
             }
             EditMessage::CurvePasted(point) => {
-                if let Some(copied) = self.copied_curve.clone() {
-                    let new = Rc::unwrap_or_clone(copied);
-                    new.borrow_mut().shape.update(ShapeMessage::Centered(point));
-                    self.curves.push(Rc::new(new));
+                let pasted = self
+                    .copied_curves
+                    .iter()
+                    .map(|copied| Rc::unwrap_or_clone(copied.clone()))
+                    .collect::<Vec<_>>();
+                let (mut ids, mut next) = (HashMap::new(), self.next_group_id());
+                for curve in &pasted {
+                    curve.borrow_mut().group.renumber(&mut ids, &mut next);
+                    curve.borrow_mut().layer = self.active_layer;
+                }
+                if let [new] = &pasted[..] {
+                    new.borrow_mut()
+                        .update(CurveMessage::Shape(ShapeMessage::Centered(point)));
+                } else {
+                    //多条曲线按整体的包围盒居中
+                    let bounds = pasted
+                        .iter()
+                        .filter_map(|curve| curve.borrow().bounds())
+                        .reduce(union);
+                    if let Some(bounds) = bounds {
+                        for curve in &pasted {
+                            curve.borrow_mut().translate(point - bounds.center());
+                        }
+                    }
+                }
+
+                let before = (pasted.len() > 1).then(|| self.snapshot());
+                let count = pasted.len();
+                self.curves.extend(pasted.into_iter().map(Rc::new));
+                match before {
+                    Some(before) => self.record_snapshot(before, None),
+                    None if count == 1 => self.record_insert(self.curves.len() - 1),
+                    None => {}
+                }
+            }
+            EditMessage::Undo => {
+                if let Some(command) = self.history.undo() {
+                    self.execute(command, true);
+                }
+            }
+            EditMessage::Redo => {
+                if let Some(command) = self.history.redo() {
+                    self.execute(command, false);
                 }
             }
+            EditMessage::HistoryDepth(depth) => self.depth_input = Some(depth),
+            EditMessage::HistoryDepthSubmitted => {
+                if let Some(Ok(depth)) = self.depth_input.take().map(|d| d.trim().parse::<usize>())
+                {
+                    self.history.set_depth(depth);
+                }
+            }
+            EditMessage::Released => self.history.close_merge(),
         }
 
         if self.pending.borrow_mut().shape.is_complete() {
@@ -125,28 +527,57 @@ impl Edit {
                         IcedCanvas::new(Pad {
                             pending: &self.pending,
                             curves: &self.curves,
+                            layers: &self.layers,
                             selected: self.selected.clone(),
                             cache: self.cache.clone(),
+                            eyedropper: self.eyedropper,
+                            viewport: self.viewport.clone(),
+                            snap: self.snap,
                         })
                         .width(Length::Fill)
                         .height(Length::Fill),
                     )
-                    .push(Space::with_height(Length::Units(10))),
+                    .push(self.view_bar()),
             )
             .push(Space::with_width(Length::Units(10)));
 
-        let editable = if let Some(index) = self.selected.borrow().0 {
-            Editable {
-                curves_len: self.curves.len(),
-                curve: &self.curves[index],
-                label: CurveLabel::Selected(index),
-            }
+        let selected = self.selected.borrow();
+        let (curve, label) = if self.editing_style.is_some() {
+            (&self.style_template, CurveLabel::Style)
+        } else if let Some(index) = selected.primary() {
+            (
+                &self.curves[index],
+                if selected.len() > 1 {
+                    CurveLabel::Multiple(index, selected.len())
+                } else {
+                    CurveLabel::Selected(index)
+                },
+            )
         } else {
-            Editable {
-                curves_len: self.curves.len(),
-                curve: &self.pending,
-                label: CurveLabel::Pending,
-            }
+            (&self.pending, CurveLabel::Pending)
+        };
+        let editable = Editable {
+            stack: Stack::rows(&self.curves)
+                .into_iter()
+                .filter(|index| is_selectable(&self.layers, &self.curves[*index].borrow()))
+                .map(|index| StackItem {
+                    index,
+                    name: self.curves[index].borrow().shape.to_string(),
+                })
+                .collect(),
+            curve,
+            selection: match label {
+                CurveLabel::Multiple(..) => selected
+                    .curves
+                    .iter()
+                    .map(|index| &self.curves[*index])
+                    .collect(),
+                _ => vec![],
+            },
+            label,
+            color_space: self.color_space,
+            eyedropper: self.eyedropper,
+            palette: self.recent_colors.clone(),
         };
 
         Row::new()
@@ -154,26 +585,1000 @@ impl Edit {
             .width(Length::Fill)
             .height(Length::Fill)
             .push(canvas)
-            .push(editable.view().map(EditMessage::Curve))
+            .push(
+                Column::new()
+                    .width(Length::FillPortion(2))
+                    .spacing(15)
+                    .push(self.history_panel())
+                    .push(self.styles_panel())
+                    .push(self.layers_panel())
+                    .push(self.transform_panel())
+                    .push(
+                        Column::new()
+                            .spacing(10)
+                            .push(Text::new("Stacking Order:"))
+                            .push(
+                                IcedCanvas::new(Stack {
+                                    curves: &self.curves,
+                                    layers: &self.layers,
+                                    selected: self.selected.clone(),
+                                })
+                                .width(Length::Fill)
+                                .height(Length::Units(
+                                    (self.curves.len().max(1) as f32 * Stack::ROW_HEIGHT) as u16,
+                                )),
+                            ),
+                    )
+                    .push(editable.view().map(EditMessage::Curve)),
+            )
             .into()
     }
 
-    fn redraw(&mut self) {
-        self.cache.borrow_mut().clear();
+    fn redraw(&mut self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            curves: self.curves.iter().map(|c| c.borrow().clone()).collect(),
+            styles: self.styles.clone(),
+            layers: self.layers.clone(),
+        }
+    }
+
+    fn record_snapshot(&mut self, before: Snapshot, key: Option<MergeKey>) {
+        let after = self.snapshot();
+        self.history
+            .push(EditCommand::Replace { before, after }, key);
+    }
+
+    fn curves_at(&self, indices: &[usize]) -> Vec<Curve> {
+        indices
+            .iter()
+            .map(|index| self.curves[*index].borrow().clone())
+            .collect()
+    }
+
+    //before是indices里的曲线修改之前的样子
+    fn modified(&self, indices: &[usize], before: Vec<Curve>) -> Vec<EditCommand> {
+        indices
+            .iter()
+            .zip(before)
+            .map(|(index, before)| EditCommand::Modify {
+                index: *index,
+                before: Box::new(before),
+                after: Box::new(self.curves[*index].borrow().clone()),
+            })
+            .collect()
+    }
+
+    fn record_modified(&mut self, indices: &[usize], before: Vec<Curve>, key: Option<MergeKey>) {
+        let commands = self.modified(indices, before);
+        self.history.push(EditCommand::Batch(commands), key);
+    }
+
+    fn record_insert(&mut self, index: usize) {
+        let curve = self.curves[index].borrow().clone();
+        self.history
+            .push(EditCommand::Insert { index, curve }, None);
+    }
+
+    //撤销时反向执行命令，下标可能已经失效，所以清空选中
+    fn execute(&mut self, command: EditCommand, undo: bool) {
+        let new = |curve: Curve| Rc::new(RefCell::new(curve));
+        match (command, undo) {
+            (EditCommand::Insert { index, .. }, true)
+            | (EditCommand::Remove { index, .. }, false) => {
+                if index < self.curves.len() {
+                    self.curves.remove(index);
+                }
+            }
+            (EditCommand::Insert { index, curve }, false)
+            | (EditCommand::Remove { index, curve }, true) => {
+                self.curves.insert(index.min(self.curves.len()), new(curve));
+            }
+            (EditCommand::Modify { index, before, .. }, true)
+            | (
+                EditCommand::Modify {
+                    index,
+                    after: before,
+                    ..
+                },
+                false,
+            ) => {
+                if let Some(curve) = self.curves.get(index) {
+                    *curve.borrow_mut() = *before;
+                }
+            }
+            (EditCommand::Replace { before, .. }, true)
+            | (EditCommand::Replace { after: before, .. }, false) => {
+                self.curves = before.curves.into_iter().map(new).collect();
+                self.styles = before.styles;
+                self.layers = before.layers;
+                self.active_layer = self.active_layer.min(self.layers.len() - 1);
+            }
+            (EditCommand::Style { index, before, .. }, true)
+            | (
+                EditCommand::Style {
+                    index,
+                    after: before,
+                    ..
+                },
+                false,
+            ) => {
+                if let Some(style) = self.styles.get_mut(index) {
+                    *style = before;
+                }
+            }
+            //撤销时倒着执行
+            (EditCommand::Batch(commands), true) => {
+                for command in commands.into_iter().rev() {
+                    self.execute(command, true);
+                }
+            }
+            (EditCommand::Batch(commands), false) => {
+                for command in commands {
+                    self.execute(command, false);
+                }
+            }
+        }
+
+        self.selected.borrow_mut().clear();
+        self.editing_style = None;
+        self.style_name_input = None;
+    }
+
+    //图层的增删和排序会改变曲线上记录的图层下标
+    fn update_layer(&mut self, message: LayerMessage) {
+        let (before, key) = (self.snapshot(), MergeKey::layer(&message));
+        //输入名字和拖动滑条时合并成一次
+        let merge = matches!(
+            message,
+            LayerMessage::Renamed(_) | LayerMessage::SlideOpacity(_)
+        );
+
+        match message {
+            LayerMessage::Activated(index) => {
+                if index < self.layers.len() {
+                    self.active_layer = index;
+                }
+                return;
+            }
+            LayerMessage::Added => {
+                let mut n = self.layers.len() + 1;
+                while self.layers.iter().any(|l| l.name == format!("Layer {}", n)) {
+                    n += 1;
+                }
+                let inserted = self.active_layer + 1;
+                self.layers
+                    .insert(inserted, Layer::new(format!("Layer {}", n)));
+                self.remap_layers(|layer| if layer >= inserted { layer + 1 } else { layer });
+                self.active_layer = inserted;
+            }
+            //图层上的曲线一起删除，至少保留一个图层
+            LayerMessage::Removed => {
+                if self.layers.len() == 1 {
+                    return;
+                }
+                let removed = self.active_layer;
+                self.layers.remove(removed);
+                self.curves.retain(|curve| curve.borrow().layer != removed);
+                self.remap_layers(|layer| if layer > removed { layer - 1 } else { layer });
+                self.active_layer = removed.saturating_sub(1);
+                self.selected.borrow_mut().clear();
+            }
+            LayerMessage::Renamed(name) => self.layers[self.active_layer].name = name,
+            LayerMessage::VisibilityToggled(index, visible) => {
+                self.layers[index].visible = visible;
+                self.deselect_layer(index);
+            }
+            LayerMessage::LockToggled(index, locked) => {
+                self.layers[index].locked = locked;
+                self.deselect_layer(index);
+            }
+            LayerMessage::Raised(index) | LayerMessage::Lowered(index) => {
+                let other = match message {
+                    LayerMessage::Raised(_) => index + 1,
+                    _ => index.wrapping_sub(1),
+                };
+                if other >= self.layers.len() {
+                    return;
+                }
+                self.layers.swap(index, other);
+                self.remap_layers(|layer| match layer {
+                    layer if layer == index => other,
+                    layer if layer == other => index,
+                    layer => layer,
+                });
+                if self.active_layer == index {
+                    self.active_layer = other;
+                } else if self.active_layer == other {
+                    self.active_layer = index;
+                }
+            }
+            LayerMessage::SlideOpacity(opacity) => {
+                self.layers[self.active_layer].opacity = opacity;
+            }
+            LayerMessage::SelectionMoved => {
+                for index in self.selected.borrow().curves.iter() {
+                    self.curves[*index].borrow_mut().layer = self.active_layer;
+                }
+            }
+        }
+
+        self.record_snapshot(before, merge.then_some(key));
+    }
+
+    //调整选中曲线的叠放顺序，上移下移一层时只越过同一图层的曲线，组作为一个整体移动
+    fn restack(&mut self, order: ZOrder) {
+        let selected = self.selected.borrow().curves.clone();
+        if selected.is_empty() {
+            return;
+        }
+        let before = self.snapshot();
+        let unchanged = self.curves.iter().map(Rc::as_ptr).collect::<Vec<_>>();
+        //每个单位记下是否选中
+        let mut units = self
+            .units()
+            .into_iter()
+            .map(|unit| {
+                let chosen = unit.iter().any(|curve| {
+                    selected
+                        .iter()
+                        .any(|index| Rc::ptr_eq(&self.curves[*index], curve))
+                });
+                (unit, chosen)
+            })
+            .collect::<Vec<_>>();
+        let layer_of = |unit: &[Rc<RefCell<Curve>>]| unit[0].borrow().layer;
+
+        match order {
+            ZOrder::Front | ZOrder::Back => {
+                let (mut moved, mut rest): (Vec<_>, Vec<_>) =
+                    units.drain(..).partition(|(_, chosen)| *chosen);
+                units = if order == ZOrder::Front {
+                    rest.append(&mut moved);
+                    rest
+                } else {
+                    moved.append(&mut rest);
+                    moved
+                };
+            }
+            //从上往下处理，已经上移的曲线不会挡住下面的
+            ZOrder::Forward => {
+                for index in (0..units.len()).rev() {
+                    if !units[index].1 {
+                        continue;
+                    }
+                    let layer = layer_of(&units[index].0);
+                    let above = (index + 1..units.len())
+                        .find(|i| !units[*i].1 && layer_of(&units[*i].0) == layer);
+                    if let Some(above) = above {
+                        let unit = units.remove(index);
+                        units.insert(above, unit);
+                    }
+                }
+            }
+            ZOrder::Backward => {
+                for index in 0..units.len() {
+                    if !units[index].1 {
+                        continue;
+                    }
+                    let layer = layer_of(&units[index].0);
+                    let below = (0..index)
+                        .rev()
+                        .find(|i| !units[*i].1 && layer_of(&units[*i].0) == layer);
+                    if let Some(below) = below {
+                        let unit = units.remove(index);
+                        units.insert(below, unit);
+                    }
+                }
+            }
+        }
+
+        //下标变了，选中移动过的组里的所有曲线
+        let mut curves = vec![];
+        let mut moved = vec![];
+        for (unit, chosen) in units {
+            if chosen {
+                moved.extend(curves.len()..curves.len() + unit.len());
+            }
+            curves.extend(unit);
+        }
+        self.curves = curves;
+        self.selected.borrow_mut().curves = moved;
+        if self.curves.iter().map(Rc::as_ptr).collect::<Vec<_>>() != unchanged {
+            self.record_snapshot(before, None);
+        }
+    }
+
+    //把from所在的组整体挪到to所在的组的上面或下面，跨图层时挪到to的图层里
+    fn reorder(&mut self, from: usize, to: usize) {
+        let (Some(source), Some(target)) = (self.curves.get(from), self.curves.get(to)) else {
+            return;
+        };
+        let (source, target) = (source.clone(), target.clone());
+        if !is_selectable(&self.layers, &source.borrow())
+            || !is_selectable(&self.layers, &target.borrow())
+        {
+            return;
+        }
+        //列表按(图层, 下标)排列，往下拖放在目标下面，往上拖放在目标上面
+        let (from_layer, to_layer) = (source.borrow().layer, target.borrow().layer);
+        let downward = (to_layer, to) < (from_layer, from);
+
+        let mut units = self.units();
+        let contains =
+            |unit: &Vec<Rc<RefCell<Curve>>>, curve| unit.iter().any(|c| Rc::ptr_eq(c, curve));
+        let Some(moving) = units.iter().position(|unit| contains(unit, &source)) else {
+            return;
+        };
+        if contains(&units[moving], &target) {
+            return;
+        }
+        let before = self.snapshot();
+        let unit = units.remove(moving);
+        let Some(position) = units.iter().position(|unit| contains(unit, &target)) else {
+            return;
+        };
+        for curve in &unit {
+            curve.borrow_mut().layer = to_layer;
+        }
+        let len = unit.len();
+        units.insert(if downward { position } else { position + 1 }, unit);
+
+        let start = units
+            .iter()
+            .take_while(|unit| !contains(unit, &source))
+            .map(Vec::len)
+            .sum::<usize>();
+        self.curves = units.into_iter().flatten().collect();
+        self.selected.borrow_mut().curves = (start..start + len).collect();
+        self.record_snapshot(before, None);
+    }
+
+    //叠放顺序里不可分开的单位，同一个最外层组里挨着的曲线是一个单位，不在组里的曲线各自是一个单位
+    fn units(&self) -> Vec<Vec<Rc<RefCell<Curve>>>> {
+        let mut units: Vec<Vec<Rc<RefCell<Curve>>>> = vec![];
+        for curve in &self.curves {
+            let group = curve.borrow().group.outermost();
+            match units.last_mut() {
+                Some(unit) if group.is_some() && unit[0].borrow().group.outermost() == group => {
+                    unit.push(curve.clone())
+                }
+                _ => units.push(vec![curve.clone()]),
+            }
+        }
+        units
+    }
+
+    fn remap_layers(&mut self, remap: impl Fn(usize) -> usize) {
+        for curve in &self.curves {
+            let mut curve = curve.borrow_mut();
+            curve.layer = remap(curve.layer);
+        }
+    }
+
+    //隐藏和锁定的图层上的曲线不能保持选中
+    fn deselect_layer(&mut self, index: usize) {
+        if !self.layers[index].is_selectable() {
+            let curves = &self.curves;
+            self.selected
+                .borrow_mut()
+                .retain(|i| curves[i].borrow().layer != index);
+        }
+    }
+
+    //按钮和输入框以选中的曲线整体的中心为中心，拖动控制框时变换里已经带了中心
+    fn update_transform(&mut self, message: TransformMessage) {
+        let center = self
+            .selected
+            .borrow()
+            .curves
+            .iter()
+            .filter_map(|index| self.curves[*index].borrow().bounds())
+            .reduce(union)
+            .map(|bounds| bounds.center());
+        let (transform, dragging) = match message {
+            TransformMessage::Apply => (self.transform_input.transform(), false),
+            TransformMessage::FlipHorizontal => (Some(Transform::scale(-1.0, 1.0)), false),
+            TransformMessage::FlipVertical => (Some(Transform::scale(1.0, -1.0)), false),
+            TransformMessage::Dragged(transform) => (Some(transform), true),
+            message => {
+                self.transform_input.update(message);
+                return;
+            }
+        };
+
+        let transform = match (transform, center, dragging) {
+            (Some(transform), _, true) => transform,
+            (Some(transform), Some(center), false) => transform.about(center),
+            _ => return,
+        };
+        let message = CurveMessage::Transform(transform);
+        let key = MergeKey::new(None, &message);
+        let indices = self.selected.borrow().curves.clone();
+        let before = self.curves_at(&indices);
+        self.update_selection(message);
+        //拖动时连续的变换合并成一次
+        self.record_modified(&indices, before, key.filter(|_| dragging));
+    }
+
+    fn next_group_id(&self) -> usize {
+        self.curves
+            .iter()
+            .filter_map(|curve| curve.borrow().group.ids().iter().max().copied())
+            .max()
+            .map_or(0, |id| id + 1)
+    }
+
+    //正在编辑的曲线，没有选中时是待添加的曲线
+    fn current(&self) -> &Rc<RefCell<Curve>> {
+        match self.selected.borrow().primary() {
+            Some(index) => &self.curves[index],
+            None => &self.pending,
+        }
+    }
+
+    //样式操作作用的曲线，没有选中时是待添加的曲线
+    fn targets(&self) -> Vec<Rc<RefCell<Curve>>> {
+        let selected = self.selected.borrow();
+        if selected.is_empty() {
+            vec![self.pending.clone()]
+        } else {
+            selected
+                .curves
+                .iter()
+                .map(|index| self.curves[*index].clone())
+                .collect()
+        }
+    }
+
+    //修改所有选中的曲线，整体移动时其余曲线跟着主要的曲线平移
+    fn update_selection(&mut self, message: CurveMessage) {
+        let selected = self.selected.borrow().curves.clone();
+        let primary = &self.curves[selected[0]];
+        if let CurveMessage::Shape(ShapeMessage::Move(handle, point)) = &message {
+            if let Some(from) = primary.borrow().points().get(handle) {
+                for index in &selected[1..] {
+                    self.curves[*index].borrow_mut().translate(*point - *from);
+                }
+            }
+            primary.borrow_mut().update(message);
+        } else {
+            for index in selected {
+                self.curves[index].borrow_mut().update(message.clone());
+            }
+        }
+    }
+
+    //样式修改之后同步到所有引用它的曲线
+    fn sync_style(&mut self, index: usize) {
+        let name = self.styles[index].name.clone();
+        self.styles[index] = NamedStyle::from_curve(name.clone(), &self.style_template.borrow());
+        for curve in self.curves.iter().chain([&self.pending]) {
+            if curve.borrow().style.as_ref() == Some(&name) {
+                self.styles[index].apply(&mut curve.borrow_mut());
+            }
+        }
+    }
+
+    //画布下面的缩放比例、缩放按钮和网格吸附的设置
+    fn view_bar(&self) -> Row<'_, EditMessage> {
+        let button = |label: &'static str, vm: ViewMessage| {
+            Button::new(Text::new(label))
+                .style(style::Button::Toolbar)
+                .on_press(EditMessage::View(vm))
+        };
+
+        Row::new()
+            .padding([5, 0])
+            .align_items(Alignment::Center)
+            .spacing(10)
+            .push(button("-", ViewMessage::ZoomOut))
+            .push(
+                Text::new(format!("{:.0}%", self.viewport.borrow().zoom * 100.0))
+                    .width(Length::Units(50)),
+            )
+            .push(button("+", ViewMessage::ZoomIn))
+            .push(button("100%", ViewMessage::Actual))
+            .push(button("Fit", ViewMessage::Fit))
+            .push(Space::with_width(Length::Units(10)))
+            .push(Checkbox::new(self.snap.grid, "Grid", |g| {
+                EditMessage::Snap(SnapMessage::GridToggled(g))
+            }))
+            .push(
+                text_input::TextInput::new(
+                    "20",
+                    format_attribute(self.snap.spacing).as_str(),
+                    |s| EditMessage::Snap(SnapMessage::InputSpacing(s)),
+                )
+                .style(style::TextInput::EditAttribute)
+                .width(Length::Units(50)),
+            )
+            .push(Checkbox::new(self.snap.to_grid, "Snap to Grid", |g| {
+                EditMessage::Snap(SnapMessage::SnapToGridToggled(g))
+            }))
+            .push(Checkbox::new(self.snap.to_points, "Snap to Points", |p| {
+                EditMessage::Snap(SnapMessage::SnapToPointsToggled(p))
+            }))
+    }
+
+    fn history_panel(&self) -> Row<'_, EditMessage> {
+        let mut undo = Button::new(Text::new("Undo")).style(style::Button::Toolbar);
+        if self.history.can_undo() {
+            undo = undo.on_press(EditMessage::Undo);
+        }
+        let mut redo = Button::new(Text::new("Redo")).style(style::Button::Toolbar);
+        if self.history.can_redo() {
+            redo = redo.on_press(EditMessage::Redo);
+        }
+
+        Row::new()
+            .align_items(Alignment::Center)
+            .spacing(10)
+            .push(undo)
+            .push(redo)
+            .push(Text::new("History Depth:  "))
+            .push(
+                text_input::TextInput::new(
+                    "100",
+                    self.depth_input
+                        .clone()
+                        .unwrap_or_else(|| self.history.depth().to_string())
+                        .as_str(),
+                    EditMessage::HistoryDepth,
+                )
+                .on_submit(EditMessage::HistoryDepthSubmitted)
+                .style(style::TextInput::EditAttribute)
+                .width(Length::Units(50)),
+            )
+    }
+
+    fn transform_panel(&self) -> Column<'_, EditMessage> {
+        let message = EditMessage::Transform;
+        let input = &self.transform_input;
+        let field =
+            |label: &'static str, value: &str, on_change: fn(String) -> TransformMessage| {
+                Row::new()
+                    .align_items(Alignment::Center)
+                    .push(Text::new(label))
+                    .push(
+                        text_input::TextInput::new("0", value, move |v| message(on_change(v)))
+                            .style(style::TextInput::EditAttribute)
+                            .width(Length::Units(50)),
+                    )
+            };
+
+        let selected = !self.selected.borrow().is_empty();
+        let button = |label: &'static str, tm: TransformMessage| {
+            let button = Button::new(Text::new(label)).style(style::Button::Toolbar);
+            if selected {
+                button.on_press(message(tm))
+            } else {
+                button
+            }
+        };
+
+        Column::new()
+            .spacing(10)
+            .push(Text::new("Transform:"))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .push(field(
+                        "Rotate:  ",
+                        &input.angle,
+                        TransformMessage::InputAngle,
+                    ))
+                    .push(field(
+                        "Scale X %:  ",
+                        &input.scale_x,
+                        TransformMessage::InputScaleX,
+                    ))
+                    .push(field(
+                        "Scale Y %:  ",
+                        &input.scale_y,
+                        TransformMessage::InputScaleY,
+                    )),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .push(field(
+                        "Skew X:  ",
+                        &input.skew_x,
+                        TransformMessage::InputSkewX,
+                    ))
+                    .push(field(
+                        "Skew Y:  ",
+                        &input.skew_y,
+                        TransformMessage::InputSkewY,
+                    )),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .push(button("Apply", TransformMessage::Apply))
+                    .push(button("Flip Horizontal", TransformMessage::FlipHorizontal))
+                    .push(button("Flip Vertical", TransformMessage::FlipVertical)),
+            )
+    }
+
+    //上面的图层排在前面
+    fn layers_panel(&self) -> Column<'_, EditMessage> {
+        let message = EditMessage::Layer;
+        let mut panel = Column::new().spacing(10).push(Text::new("Layers:"));
+
+        for (index, layer) in self.layers.iter().enumerate().rev() {
+            let mut raise = Button::new(Text::new("Up")).style(style::Button::Toolbar);
+            if index + 1 < self.layers.len() {
+                raise = raise.on_press(message(LayerMessage::Raised(index)));
+            }
+            let mut lower = Button::new(Text::new("Down")).style(style::Button::Toolbar);
+            if index > 0 {
+                lower = lower.on_press(message(LayerMessage::Lowered(index)));
+            }
+
+            panel = panel.push(
+                Row::new()
+                    .align_items(Alignment::Center)
+                    .spacing(10)
+                    .push(
+                        Button::new(Text::new(layer.name.as_str()))
+                            .style(if index == self.active_layer {
+                                style::Button::Confirm
+                            } else {
+                                style::Button::Toolbar
+                            })
+                            .on_press(message(LayerMessage::Activated(index))),
+                    )
+                    .push(Checkbox::new(layer.visible, "Visible", move |v| {
+                        message(LayerMessage::VisibilityToggled(index, v))
+                    }))
+                    .push(Checkbox::new(layer.locked, "Locked", move |l| {
+                        message(LayerMessage::LockToggled(index, l))
+                    }))
+                    .push(raise)
+                    .push(lower),
+            );
+        }
+
+        let active = &self.layers[self.active_layer];
+        let mut remove = Button::new(Text::new("Delete Layer")).style(style::Button::Delete);
+        if self.layers.len() > 1 {
+            remove = remove.on_press(message(LayerMessage::Removed));
+        }
+        let mut move_here =
+            Button::new(Text::new("Move Selection Here")).style(style::Button::Toolbar);
+        if !self.selected.borrow().is_empty() {
+            move_here = move_here.on_press(message(LayerMessage::SelectionMoved));
+        }
+
+        panel
+            .push(
+                Row::new()
+                    .align_items(Alignment::Center)
+                    .spacing(10)
+                    .push(Text::new("Name:  "))
+                    .push(
+                        text_input::TextInput::new("name", active.name.as_str(), move |n| {
+                            message(LayerMessage::Renamed(n))
+                        })
+                        .style(style::TextInput::EditAttribute)
+                        .width(Length::Units(120)),
+                    )
+                    .push(Text::new("Opacity:  "))
+                    .push(
+                        Slider::new(0.0..=1.0, active.opacity, move |o| {
+                            message(LayerMessage::SlideOpacity(o))
+                        })
+                        .on_release(EditMessage::Released)
+                        .step(0.01),
+                    )
+                    .push(Text::new(format!("{:.2}", active.opacity))),
+            )
+            .push(
+                Row::new()
+                    .align_items(Alignment::Center)
+                    .spacing(10)
+                    .push(
+                        Button::new(Text::new("Add Layer"))
+                            .style(style::Button::Confirm)
+                            .on_press(message(LayerMessage::Added)),
+                    )
+                    .push(remove)
+                    .push(move_here),
+            )
+    }
+
+    fn styles_panel(&self) -> Column<'_, EditMessage> {
+        //选中的曲线用了不同的样式时不显示
+        let targets = self.targets();
+        let current = targets[0].borrow().style.clone();
+        let current = if targets.iter().all(|c| c.borrow().style == current) {
+            current
+        } else {
+            None
+        };
+        let names = self
+            .styles
+            .iter()
+            .map(|s| s.name.clone())
+            .collect::<Vec<_>>();
+
+        let mut panel = Column::new().spacing(10).push(
+            Row::new()
+                .align_items(Alignment::Center)
+                .spacing(10)
+                .push(Text::new("Style:  "))
+                .push(
+                    PickList::new(names, current.clone(), EditMessage::StyleApplied)
+                        .placeholder("none")
+                        .style(style::PickList),
+                )
+                .push(if current.is_some() {
+                    Button::new(Text::new("Detach"))
+                        .style(style::Button::Delete)
+                        .on_press(EditMessage::StyleDetached)
+                } else {
+                    Button::new(Text::new("Save as Style"))
+                        .style(style::Button::Confirm)
+                        .on_press(EditMessage::StyleCreated)
+                }),
+        );
+
+        if !self.styles.is_empty() {
+            panel = panel.push(
+                self.styles.iter().enumerate().fold(
+                    Row::new()
+                        .align_items(Alignment::Center)
+                        .spacing(5)
+                        .push(Text::new("Edit:  ")),
+                    |acc, (index, named)| {
+                        let editing = self.editing_style == Some(index);
+                        acc.push(
+                            Button::new(Text::new(named.name.as_str()))
+                                .style(if editing {
+                                    style::Button::Confirm
+                                } else {
+                                    style::Button::Toolbar
+                                })
+                                .on_press(EditMessage::StyleEdited(if editing {
+                                    None
+                                } else {
+                                    Some(index)
+                                })),
+                        )
+                    },
+                ),
+            );
+        }
+
+        if let Some(index) = self.editing_style {
+            let name = self
+                .style_name_input
+                .clone()
+                .unwrap_or_else(|| self.styles[index].name.clone());
+            panel = panel.push(
+                Row::new()
+                    .align_items(Alignment::Center)
+                    .spacing(10)
+                    .push(Text::new("Name:  "))
+                    .push(
+                        text_input::TextInput::new(
+                            "name",
+                            name.as_str(),
+                            EditMessage::StyleRenamed,
+                        )
+                        .style(style::TextInput::EditAttribute)
+                        .width(Length::Units(120)),
+                    )
+                    .push(
+                        Button::new(Text::new("Done"))
+                            .style(style::Button::Confirm)
+                            .on_press(EditMessage::StyleEdited(None)),
+                    )
+                    .push(
+                        Button::new(Text::new("Delete"))
+                            .style(style::Button::Delete)
+                            .on_press(EditMessage::StyleRemoved),
+                    ),
+            );
+        }
+
+        panel
+    }
+
+    pub fn is_picking(&self) -> bool {
+        self.eyedropper.is_some()
+    }
+
+    //选定或者输入的颜色放到最近用过的颜色的最前面，同一个颜色只留一个
+    fn remember_color(&mut self, message: &EditMessage) {
+        let (target, color) = match message {
+            EditMessage::Curve(
+                CurveMessage::SetColor(target, color) | CurveMessage::SwatchPicked(target, color),
+            ) => (*target, Some(*color)),
+            EditMessage::Curve(CurveMessage::InputHex(target, input)) => {
+                (*target, parse_color(input))
+            }
+            _ => {
+                self.recoloring = None;
+                return;
+            }
+        };
+
+        if let Some(color) = color {
+            //连续修改同一个目标时，替换掉这次修改中间的颜色
+            if self.recoloring == Some(target) && !self.recent_colors.is_empty() {
+                self.recent_colors.remove(0);
+            }
+            self.recent_colors
+                .retain(|c| get_format_color(*c) != get_format_color(color));
+            self.recent_colors.insert(0, color);
+            self.recent_colors.truncate(Edit::PALETTE_SIZE);
+        }
+        self.recoloring = Some(target);
+    }
+
+    pub fn export(&self) {
+        if let Some(pathbuf) = save_file() {
+            let defs = self
+                .curves
+                .iter()
+                .enumerate()
+                .flat_map(|(id, x)| x.borrow().defs(id))
+                .collect::<Vec<_>>();
+
+            let document = if defs.is_empty() {
+                Document::new()
+            } else {
+                Document::new().add(
+                    defs.into_iter()
+                        .fold(Definitions::new(), |acc, x| acc.add(x)),
+                )
+            };
+
+            //共用样式导出为css类
+            let document = if self.styles.is_empty() {
+                document
+            } else {
+                document.add(Style::new(
+                    self.styles
+                        .iter()
+                        .map(NamedStyle::export_as_css)
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ))
+            };
+
+            //每个图层导出为一个<g>，组导出为图层里嵌套的<g>
+            let document = self.layers.iter().enumerate().fold(
+                document
+                    .set("xmlns:inkscape", Layer::INKSCAPE)
+                    .set("xmlns:sodipodi", Layer::SODIPODI),
+                |acc, (index, layer)| {
+                    let mut group = layer.export_as_svg(index);
+                    Group::export(
+                        &mut group,
+                        self.curves.iter().enumerate().filter_map(|(id, x)| {
+                            let curve = x.borrow();
+                            if curve.layer == index {
+                                curve.save(id).map(|path| (curve.group.clone(), path))
+                            } else {
+                                None
+                            }
+                        }),
+                    );
+                    acc.add(group)
+                },
+            );
+
+            svg::save(pathbuf, &document).unwrap();
+        }
+    }
+}
+
+//选中的曲线，第一条是主要的曲线，编辑界面显示它的数值，拖动的点也属于它
+#[derive(Debug, Default, Clone)]
+struct Selection {
+    curves: Vec<usize>,
+    point: Option<String>,
+}
+
+impl Selection {
+    fn primary(&self) -> Option<usize> {
+        self.curves.first().copied()
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        self.curves.contains(&index)
+    }
+
+    fn len(&self) -> usize {
+        self.curves.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.curves.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.curves.clear();
+        self.point = None;
+    }
+
+    //只选中这一条曲线
+    fn single(&mut self, index: usize, point: Option<String>) {
+        self.curves = vec![index];
+        self.point = point;
     }
 
-    pub fn export(&self) {
-        if let Some(pathbuf) = save_file() {
-            let document = self.curves.iter().fold(Document::new(), |acc, x| {
-                if let Some(path) = x.borrow().save() {
-                    acc.add(path)
-                } else {
-                    acc
-                }
-            });
+    //加入选中并作为主要的曲线，已经选中的曲线只是换到最前面
+    fn focus(&mut self, index: usize, point: Option<String>) {
+        self.curves.retain(|i| *i != index);
+        self.curves.insert(0, index);
+        self.point = point;
+    }
 
-            svg::save(pathbuf, &document).unwrap();
+    //框选或者选中组时加入，不改变主要的曲线
+    fn extend(&mut self, indices: Vec<usize>) {
+        for index in indices {
+            if !self.contains(index) {
+                self.curves.push(index);
+            }
+        }
+    }
+
+    fn remove(&mut self, index: usize) {
+        if self.primary() == Some(index) {
+            self.point = None;
+        }
+        self.curves.retain(|i| *i != index);
+    }
+
+    fn retain(&mut self, keep: impl Fn(usize) -> bool) {
+        if self.primary().is_some_and(|primary| !keep(primary)) {
+            self.point = None;
         }
+        self.curves.retain(|index| keep(*index));
+    }
+
+    //按文档里的顺序，删除和复制时使用
+    fn sorted(&self) -> Vec<usize> {
+        let mut sorted = self.curves.clone();
+        sorted.sort_unstable();
+        sorted
+    }
+}
+
+//隐藏和锁定的图层上的曲线不能选中
+fn is_selectable(layers: &[Layer], curve: &Curve) -> bool {
+    layers.get(curve.layer).is_none_or(Layer::is_selectable)
+}
+
+//和这条曲线在同一个最外层组里的所有曲线，组作为一个整体选中
+fn members(curves: &[Rc<RefCell<Curve>>], index: usize) -> Vec<usize> {
+    match curves[index].borrow().group.outermost() {
+        Some(id) => curves
+            .iter()
+            .enumerate()
+            .filter(|(_, curve)| curve.borrow().group.outermost() == Some(id))
+            .map(|(index, _)| index)
+            .collect(),
+        None => vec![index],
+    }
+}
+
+//两个矩形的包围盒
+fn union(a: IcedRectangle, b: IcedRectangle) -> IcedRectangle {
+    let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+    IcedRectangle {
+        x,
+        y,
+        width: (a.x + a.width).max(b.x + b.width) - x,
+        height: (a.y + a.height).max(b.y + b.height) - y,
     }
 }
 
@@ -181,16 +1586,56 @@ impl Edit {
 pub struct Interaction {
     curve_to_select: Option<Rc<RefCell<Curve>>>, //这里的原因是Edit可能会改变向量长度，而这里是独立更新的，因此可能会越界
     pressed_point: Option<Point>,
+    last_click: Option<(Instant, Point)>,
     ctrl_pressed: bool,
+    shift_pressed: bool,
+    //在空白处拖动时框选的另一个角
+    marquee: Option<Point>,
+    //按住shift点到已经选中的曲线，松开时如果没有拖动就取消选中
+    to_deselect: Option<usize>,
+    //正在拖动的控制框把手，上一次的位置和不动的中心
+    gizmo: Option<(Handle, Point, Point)>,
+    //按住空格时拖动左键平移画布
+    space_pressed: bool,
+    //正在平移画布时上一次的光标位置，是画布上的坐标
+    panning: Option<Point>,
+    //按住alt时暂时不吸附
+    alt_pressed: bool,
+    //拖动点时的对齐参考线
+    guides: Vec<Guide>,
+    //用钢笔工具在空白处按下，松开时放下第一个节点，拖动时不框选而是拉出控制点
+    placing: bool,
+}
+
+impl Interaction {
+    //判断这次点击和上一次点击能否构成双击，同时记录下这次点击
+    fn double_clicked(&mut self, position: Point, tolerance: f32) -> bool {
+        let now = Instant::now();
+        let double_clicked = matches!(
+            self.last_click,
+            Some((time, last)) if now.duration_since(time) < Pad::DOUBLE_CLICK_INTERVAL
+                && last.distance(position) < tolerance
+        );
+        self.last_click = if double_clicked {
+            None
+        } else {
+            Some((now, position))
+        };
+        double_clicked
+    }
 }
 
 #[derive(Debug)]
 struct Pad<'a> {
     pending: &'a Rc<RefCell<Curve>>,
     curves: &'a Vec<Rc<RefCell<Curve>>>,
+    layers: &'a [Layer],
 
-    selected: Rc<RefCell<(Option<usize>, Option<String>)>>,
+    selected: Rc<RefCell<Selection>>,
     cache: Rc<RefCell<Cache>>,
+    eyedropper: Option<ColorTarget>,
+    viewport: Rc<RefCell<Viewport>>,
+    snap: Snap,
 }
 
 impl<'a> Program<EditMessage> for Pad<'a> {
@@ -202,11 +1647,57 @@ impl<'a> Program<EditMessage> for Pad<'a> {
         bounds: IcedRectangle<f32>,
         cursor: Cursor,
     ) -> (event::Status, Option<EditMessage>) {
-        let cursor_position = if let Some(position) = cursor.position_in(&bounds) {
+        let screen_position = if let Some(position) = cursor.position_in(&bounds) {
             position
         } else {
             return (event::Status::Ignored, None);
         };
+        self.viewport.borrow_mut().size = bounds.size();
+
+        //平移和缩放画布，添加曲线的过程中也可以用
+        if let Some(status) = self.navigate(state, &event, screen_position) {
+            self.redraw();
+            return (status, None);
+        }
+        //后面都用文档里的坐标，判断距离时用换算到文档里的容差
+        let cursor_position = self.viewport.borrow().to_document(screen_position);
+        let tolerance = self.tolerance();
+
+        //吸管打开时点击只用来取色，不改变选中的曲线
+        if let (Some(target), Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))) =
+            (self.eyedropper, event)
+        {
+            if let Some(index) = self.decide_which_curve(cursor_position).0 {
+                let curve = self.curves[index].borrow();
+                let color = match target {
+                    ColorTarget::Stroke => curve.color,
+                    ColorTarget::Fill => curve.fill.unwrap_or(curve.color),
+                };
+                return (
+                    event::Status::Captured,
+                    Some(EditMessage::ColorPicked(color)),
+                );
+            }
+            return (event::Status::Captured, None);
+        }
+
+        //ctrl+z撤销，ctrl+shift+z或ctrl+y重做
+        if let Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code,
+            modifiers,
+        }) = event
+        {
+            if modifiers.contains(Modifiers::CTRL) {
+                match key_code {
+                    KeyCode::Z if modifiers.contains(Modifiers::SHIFT) => {
+                        return (event::Status::Captured, Some(EditMessage::Redo));
+                    }
+                    KeyCode::Z => return (event::Status::Captured, Some(EditMessage::Undo)),
+                    KeyCode::Y => return (event::Status::Captured, Some(EditMessage::Redo)),
+                    _ => {}
+                }
+            }
+        }
 
         if !self.pending.borrow().shape.is_empty() {
             //创建新的曲线，这个时候很多事件响应都取消了
@@ -215,18 +1706,29 @@ impl<'a> Program<EditMessage> for Pad<'a> {
                 Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
                     state.pressed_point = Some(cursor_position);
                 }
+                //自由绘制时按住左键移动就记录采样点
+                Event::Mouse(mouse::Event::CursorMoved { position: _ }) => {
+                    if state.pressed_point.is_some() && self.is_freehand() {
+                        return (
+                            event::Status::Captured,
+                            Some(EditMessage::Curve(CurveMessage::Shape(
+                                ShapeMessage::Sample(cursor_position),
+                            ))),
+                        );
+                    }
+                }
                 Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
-                    if let Some(pressed) = state.pressed_point {
-                        if pressed.distance(cursor_position) < Pad::DETERMINANT_DISTANCE {
-                            return (
-                                event::Status::Captured,
-                                Some(EditMessage::AddWithClick(cursor_position)),
-                            );
+                    if self.is_freehand() {
+                        state.pressed_point = None;
+                        return (event::Status::Captured, Some(EditMessage::AddFromPending));
+                    }
+                    if let Some(pressed) = state.pressed_point.take() {
+                        if let Some(message) = self.place(state, pressed, cursor_position) {
+                            return (event::Status::Captured, Some(message));
                         }
                     }
-                    state.pressed_point = None;
                 }
-                //按下esc放弃这次添加
+                //按下esc放弃这次添加，按下enter结束这次添加
                 Event::Keyboard(keyboard::Event::KeyPressed {
                     key_code,
                     modifiers,
@@ -237,6 +1739,9 @@ impl<'a> Program<EditMessage> for Pad<'a> {
                             Some(EditMessage::Curve(CurveMessage::Shape(ShapeMessage::Reset))),
                         );
                     }
+                    if key_code == KeyCode::Enter && modifiers.is_empty() {
+                        return (event::Status::Captured, Some(EditMessage::AddFromPending));
+                    }
                 }
                 _ => {}
             }
@@ -244,32 +1749,59 @@ impl<'a> Program<EditMessage> for Pad<'a> {
             match event {
                 Event::Mouse(mouse_event) => match mouse_event {
                     mouse::Event::CursorMoved { position: _ } => {
+                        if let Some((handle, last, pivot)) = &mut state.gizmo {
+                            let transform = handle.drag(*pivot, *last, cursor_position);
+                            if transform.is_some() {
+                                *last = cursor_position;
+                            }
+                            return (
+                                event::Status::Captured,
+                                transform
+                                    .map(|t| EditMessage::Transform(TransformMessage::Dragged(t))),
+                            );
+                        }
+
                         //查看是否有最近的点，意味着已经按下左键但未松开
-                        if state.pressed_point.is_some() {
-                            if let Some(point) = self.selected.borrow().1.as_ref() {
+                        if let Some(pressed) = state.pressed_point {
+                            if pressed.distance(cursor_position) >= tolerance {
+                                state.to_deselect = None;
+                            }
+                            if let Some(point) = self.selected.borrow().point.as_ref() {
+                                //正在拖动的曲线自己的点不参与吸附
+                                let (snapped, guides) = self.snap(
+                                    state,
+                                    cursor_position,
+                                    &self.selected.borrow().curves,
+                                );
+                                state.guides = guides;
+                                //按住ctrl时整体移动曲线，加入选中只用shift
                                 if state.ctrl_pressed {
                                     return (
                                         event::Status::Captured,
                                         Some(EditMessage::Curve(CurveMessage::Shape(
-                                            ShapeMessage::Move(point.clone(), cursor_position),
+                                            ShapeMessage::Move(point.clone(), snapped),
                                         ))),
                                     );
                                 } else {
                                     return (
                                         event::Status::Captured,
                                         Some(EditMessage::Curve(CurveMessage::Shape(
-                                            ShapeMessage::MovePoint(point.clone(), cursor_position),
+                                            ShapeMessage::MovePoint(point.clone(), snapped),
                                         ))),
                                     );
                                 }
+                            } else if pressed.distance(cursor_position) >= tolerance
+                                && !state.placing
+                            {
+                                state.marquee = Some(cursor_position);
                             }
                         }
 
                         //如果离得远了就取消预览
                         if let Some(to_select) = state.curve_to_select.clone() {
                             let mut to_cancel = true;
-                            for (_, point) in to_select.borrow().shape.points() {
-                                if point.distance(cursor_position) < Pad::DETERMINANT_DISTANCE {
+                            for (_, point) in to_select.borrow().points() {
+                                if point.distance(cursor_position) < tolerance {
                                     to_cancel = false;
                                 }
                             }
@@ -286,24 +1818,114 @@ impl<'a> Program<EditMessage> for Pad<'a> {
                         }
                     }
                     mouse::Event::ButtonPressed(mouse::Button::Left) => {
+                        //选中的曲线自己的点优先，其次是控制框的把手
+                        let hit = self.decide_which_curve(cursor_position);
+                        let on_selected =
+                            matches!(hit.0, Some(index) if self.selected.borrow().contains(index));
+                        if let (Some(gizmo), false) = (self.gizmo(), on_selected) {
+                            if let Some(handle) = gizmo.handle_at(cursor_position, tolerance) {
+                                state.gizmo = Some((handle, cursor_position, gizmo.pivot(handle)));
+                                return (event::Status::Captured, None);
+                            }
+                        }
+
                         state.pressed_point = Some(cursor_position);
-                        *self.selected.borrow_mut() = self.decide_which_curve(cursor_position);
+                        let adding = state.shift_pressed;
+                        let mut selected = self.selected.borrow_mut();
+                        match hit {
+                            (Some(index), point) if adding && selected.contains(index) => {
+                                selected.focus(index, point);
+                                state.to_deselect = Some(index);
+                            }
+                            (Some(index), point) if adding => {
+                                selected.focus(index, point);
+                                selected.extend(members(self.curves, index));
+                            }
+                            //点到已经选中的曲线时保留其余的选中，方便整体拖动
+                            (Some(index), point) if selected.contains(index) => {
+                                selected.focus(index, point)
+                            }
+                            (Some(index), point) => {
+                                selected.single(index, point);
+                                selected.extend(members(self.curves, index));
+                            }
+                            (None, _) if adding => selected.point = None,
+                            (None, _) => selected.clear(),
+                        }
+
+                        //自由绘制在按下时就开始，不需要等到松开
+                        if selected.is_empty() && self.is_freehand() {
+                            return (
+                                event::Status::Captured,
+                                Some(EditMessage::AddWithClick(cursor_position)),
+                            );
+                        }
+                        //钢笔工具的第一个节点也可以拖出控制点
+                        if hit.0.is_none() && !adding && self.is_path() {
+                            state.placing = true;
+                            return (event::Status::Captured, None);
+                        }
                     }
                     mouse::Event::ButtonReleased(mouse::Button::Left) => {
-                        if let Some(pressed) = state.pressed_point {
-                            if pressed.distance(cursor_position) < Pad::DETERMINANT_DISTANCE {
-                                *self.selected.borrow_mut() =
-                                    self.decide_which_curve(cursor_position);
-
-                                if self.selected.borrow().0.is_none() {
-                                    return (
-                                        event::Status::Captured,
-                                        Some(EditMessage::AddWithClick(cursor_position)),
-                                    );
+                        if state.gizmo.take().is_some() {
+                            return (event::Status::Captured, Some(EditMessage::Released));
+                        }
+                        if std::mem::take(&mut state.placing) {
+                            let message = state
+                                .pressed_point
+                                .take()
+                                .and_then(|pressed| self.place(state, pressed, cursor_position));
+                            return (event::Status::Captured, message);
+                        }
+                        state.guides.clear();
+                        let adding = state.shift_pressed;
+                        let to_deselect = state.to_deselect.take();
+                        if let Some(pressed) = state.pressed_point.take() {
+                            let mut selected = self.selected.borrow_mut();
+                            if state.marquee.take().is_some() {
+                                //框选包围盒和选框相交的曲线，按住shift时加入已有的选中
+                                let marquee = Pad::marquee(pressed, cursor_position);
+                                if !adding {
+                                    selected.clear();
+                                }
+                                for (index, curve) in self.curves.iter().enumerate() {
+                                    if !is_selectable(self.layers, &curve.borrow()) {
+                                        continue;
+                                    }
+                                    if let Some(bounds) = curve.borrow().bounds() {
+                                        if bounds.intersection(&marquee).is_some() {
+                                            selected.extend(members(self.curves, index));
+                                        }
+                                    }
+                                }
+                            } else if pressed.distance(cursor_position) < tolerance {
+                                match self.decide_which_curve(cursor_position) {
+                                    (Some(index), _) if to_deselect == Some(index) => {
+                                        for member in members(self.curves, index) {
+                                            selected.remove(member);
+                                        }
+                                    }
+                                    _ if adding => {}
+                                    (Some(index), point) => {
+                                        selected.single(index, point);
+                                        selected.extend(members(self.curves, index));
+                                    }
+                                    (None, _) => {
+                                        selected.clear();
+                                        if self.is_variable() {
+                                            state.double_clicked(cursor_position, tolerance);
+                                        }
+                                        let (point, _) = self.snap(state, cursor_position, &[]);
+                                        return (
+                                            event::Status::Captured,
+                                            Some(EditMessage::AddWithClick(point)),
+                                        );
+                                    }
                                 }
                             }
                         }
-                        state.pressed_point = None;
+                        //松开鼠标，结束拖动控制点的合并
+                        return (event::Status::Ignored, Some(EditMessage::Released));
                     }
                     _ => {}
                 },
@@ -315,6 +1937,45 @@ impl<'a> Program<EditMessage> for Pad<'a> {
                         if key_code == KeyCode::LControl || key_code == KeyCode::RControl {
                             state.ctrl_pressed = true;
                         }
+                        if key_code == KeyCode::LShift || key_code == KeyCode::RShift {
+                            state.shift_pressed = true;
+                        }
+
+                        //调整叠放顺序
+                        let order = match key_code {
+                            KeyCode::PageUp => Some(ZOrder::Forward),
+                            KeyCode::PageDown => Some(ZOrder::Backward),
+                            KeyCode::Home => Some(ZOrder::Front),
+                            KeyCode::End => Some(ZOrder::Back),
+                            _ => None,
+                        };
+                        if let (Some(order), true) = (order, modifiers.is_empty()) {
+                            return (event::Status::Captured, Some(EditMessage::Restack(order)));
+                        }
+
+                        //ctrl+0适应内容，ctrl+1恢复到100%，ctrl+加减号缩放
+                        let view = match key_code {
+                            KeyCode::Key0 => Some(ViewMessage::Fit),
+                            KeyCode::Key1 => Some(ViewMessage::Actual),
+                            KeyCode::Equals | KeyCode::Plus => Some(ViewMessage::ZoomIn),
+                            KeyCode::Minus => Some(ViewMessage::ZoomOut),
+                            _ => None,
+                        };
+                        if let (Some(view), true) = (view, modifiers.contains(Modifiers::CTRL)) {
+                            return (event::Status::Captured, Some(EditMessage::View(view)));
+                        }
+
+                        //ctrl+g编组，ctrl+shift+g解散最外层的组
+                        if key_code == KeyCode::G && modifiers.contains(Modifiers::CTRL) {
+                            return (
+                                event::Status::Captured,
+                                Some(if modifiers.contains(Modifiers::SHIFT) {
+                                    EditMessage::Ungroup
+                                } else {
+                                    EditMessage::Group
+                                }),
+                            );
+                        }
 
                         if key_code == KeyCode::C && modifiers.contains(Modifiers::CTRL) {
                             return (event::Status::Captured, Some(EditMessage::CurveCopied));
@@ -327,31 +1988,397 @@ impl<'a> Program<EditMessage> for Pad<'a> {
                             );
                         }
 
-                        if key_code == KeyCode::Escape
-                            && modifiers.is_empty()
-                            && (self.selected.borrow().0.is_some()
-                                || self.selected.borrow().1.is_some())
-                        {
-                            *self.selected.borrow_mut() = (None, None);
-                        }
-                    }
-                    keyboard::Event::KeyReleased {
-                        key_code,
-                        modifiers,
-                    } => {
-                        if key_code == KeyCode::LControl || key_code == KeyCode::RControl {
-                            state.ctrl_pressed = false;
-                        }
-                        if key_code == KeyCode::Delete && modifiers.is_empty() {
-                            return (event::Status::Captured, Some(EditMessage::RemoveCurve));
-                        }
-                    }
-                    _ => {}
-                },
+                        if key_code == KeyCode::Escape
+                            && modifiers.is_empty()
+                            && !self.selected.borrow().is_empty()
+                        {
+                            self.selected.borrow_mut().clear();
+                        }
+                    }
+                    keyboard::Event::KeyReleased {
+                        key_code,
+                        modifiers,
+                    } => {
+                        if key_code == KeyCode::LControl || key_code == KeyCode::RControl {
+                            state.ctrl_pressed = false;
+                        }
+                        if key_code == KeyCode::LShift || key_code == KeyCode::RShift {
+                            state.shift_pressed = false;
+                        }
+                        if key_code == KeyCode::Delete && modifiers.is_empty() {
+                            return (event::Status::Captured, Some(EditMessage::RemoveCurve));
+                        }
+                    }
+                    _ => {}
+                },
+            }
+        }
+
+        self.redraw();
+        (event::Status::Ignored, None)
+    }
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        bounds: IcedRectangle<f32>,
+        cursor: Cursor,
+    ) -> Vec<Geometry> {
+        self.viewport.borrow_mut().size = bounds.size();
+        let viewport = *self.viewport.borrow();
+        let zoom = viewport.zoom;
+        let content = self
+            .cache
+            .borrow()
+            .draw(bounds.size(), |frame: &mut Frame| {
+                //文档里的内容按视图平移缩放之后再画
+                frame.with_save(|frame| {
+                    frame.translate(viewport.offset);
+                    frame.scale(zoom);
+
+                    if self.snap.grid {
+                        let top_left = viewport.to_document(Point::ORIGIN);
+                        let bottom_right =
+                            viewport.to_document(Point::new(bounds.width, bounds.height));
+                        self.snap.draw_grid(
+                            frame,
+                            IcedRectangle::new(
+                                top_left,
+                                iced::Size::new(
+                                    bottom_right.x - top_left.x,
+                                    bottom_right.y - top_left.y,
+                                ),
+                            ),
+                            zoom,
+                        );
+                    }
+
+                    //按图层从下往上画，隐藏的图层不画
+                    let selected = self.selected.borrow();
+                    for (layer_index, layer) in self.layers.iter().enumerate() {
+                        if !layer.visible {
+                            continue;
+                        }
+                        for (index, curve) in self.curves.iter().enumerate() {
+                            let curve = curve.borrow();
+                            if curve.layer != layer_index {
+                                continue;
+                            }
+                            if layer.opacity < 1.0 {
+                                let mut faded = curve.clone();
+                                faded.opacity *= layer.opacity;
+                                faded.draw(frame, selected.contains(index), zoom);
+                            } else {
+                                curve.draw(frame, selected.contains(index), zoom);
+                            }
+                        }
+                    }
+
+                    //现在curve to select是交互内的逻辑，因此绘制也放在这里
+                    if let Some(curve_to_select) = &state.curve_to_select {
+                        curve_to_select.borrow().draw(frame, true, zoom);
+                    }
+
+                    if let Some(cursor_position) = cursor.position_in(&bounds) {
+                        let (cursor_position, _) =
+                            self.snap(state, viewport.to_document(cursor_position), &[]);
+                        self.pending.borrow().preview(frame, cursor_position, zoom);
+                    }
+
+                    Snap::draw_guides(frame, &state.guides, zoom);
+
+                    if let Some(gizmo) = self.gizmo() {
+                        gizmo.draw(frame);
+                    }
+
+                    if let (Some(pressed), Some(corner)) = (state.pressed_point, state.marquee) {
+                        let marquee = Pad::marquee(pressed, corner);
+                        let path = Path::rectangle(marquee.position(), marquee.size());
+                        frame.fill(&path, Color::from_rgba(0.2, 0.5, 1.0, 0.1));
+                        frame.stroke(
+                            &path,
+                            Stroke::default().with_color(Color::from_rgb(0.2, 0.5, 1.0)),
+                        );
+                    }
+                });
+
+                frame.stroke(
+                    &Path::rectangle(Point::ORIGIN, frame.size()),
+                    Stroke::default(),
+                );
+            });
+
+        vec![content]
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        bounds: IcedRectangle<f32>,
+        cursor: Cursor,
+    ) -> mouse::Interaction {
+        if cursor.is_over(&bounds) {
+            let hovered = self.gizmo().and_then(|gizmo| {
+                cursor.position_in(&bounds).and_then(|p| {
+                    gizmo.handle_at(self.viewport.borrow().to_document(p), self.tolerance())
+                })
+            });
+            if state.panning.is_some() {
+                mouse::Interaction::Grabbing
+            } else if state.space_pressed {
+                mouse::Interaction::Grab
+            } else if self.eyedropper.is_some() {
+                mouse::Interaction::Crosshair
+            } else if state.gizmo.is_some() {
+                mouse::Interaction::Grabbing
+            } else if let Some(handle) = hovered {
+                match handle {
+                    Handle::Scale(_, 0) => mouse::Interaction::ResizingHorizontally,
+                    Handle::Scale(0, _) => mouse::Interaction::ResizingVertically,
+                    Handle::Scale(..) => mouse::Interaction::Crosshair,
+                    Handle::Rotate => mouse::Interaction::Grab,
+                }
+            } else if !self.selected.borrow().is_empty() && state.ctrl_pressed {
+                mouse::Interaction::Grabbing
+            } else if state.curve_to_select.is_some() {
+                mouse::Interaction::Pointer
+            } else {
+                mouse::Interaction::Crosshair
+            }
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+impl<'a> Pad<'a> {
+    const DETERMINANT_DISTANCE: f32 = 10.0;
+    const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+    //点击的容差在画布上是固定的，换算到文档里随缩放变化
+    fn tolerance(&self) -> f32 {
+        Pad::DETERMINANT_DISTANCE / self.viewport.borrow().zoom
+    }
+
+    //平移和缩放画布，返回None时继续处理这个事件
+    fn navigate(
+        &self,
+        state: &mut Interaction,
+        event: &Event,
+        position: Point,
+    ) -> Option<event::Status> {
+        let mut viewport = self.viewport.borrow_mut();
+        match event {
+            Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                state.ctrl_pressed = modifiers.contains(Modifiers::CTRL);
+                state.shift_pressed = modifiers.contains(Modifiers::SHIFT);
+                state.alt_pressed = modifiers.contains(Modifiers::ALT);
+                None
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: KeyCode::Space,
+                ..
+            }) => {
+                state.space_pressed = true;
+                None
+            }
+            Event::Keyboard(keyboard::Event::KeyReleased {
+                key_code: KeyCode::Space,
+                ..
+            }) => {
+                state.space_pressed = false;
+                None
+            }
+            //ctrl+滚轮以光标为中心缩放
+            Event::Mouse(mouse::Event::WheelScrolled { delta }) if state.ctrl_pressed => {
+                let lines = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => *y,
+                    mouse::ScrollDelta::Pixels { y, .. } => *y / 50.0,
+                };
+                viewport.zoom_at(Viewport::STEP.powf(lines), position);
+                Some(event::Status::Captured)
+            }
+            //中键拖动或者按住空格用左键拖动来平移
+            Event::Mouse(mouse::Event::ButtonPressed(button))
+                if *button == mouse::Button::Middle
+                    || (*button == mouse::Button::Left && state.space_pressed) =>
+            {
+                state.panning = Some(position);
+                Some(event::Status::Captured)
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                let last = state.panning.as_mut()?;
+                viewport.pan(position - *last);
+                *last = position;
+                Some(event::Status::Captured)
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(
+                mouse::Button::Middle | mouse::Button::Left,
+            )) => state.panning.take().map(|_| event::Status::Captured),
+            _ => None,
+        }
+    }
+
+    fn decide_which_curve(&self, cursor_position: Point) -> (Option<usize>, Option<String>) {
+        let mut res = (None, None);
+        let tolerance = self.tolerance();
+        let mut last_distance = tolerance;
+        for (curves_index, curve) in self.curves.iter().enumerate() {
+            //隐藏和锁定的图层上的曲线选不中
+            if !is_selectable(self.layers, &curve.borrow()) {
+                continue;
+            }
+            for (points_index, point) in curve.borrow().points() {
+                let distance = point.distance(cursor_position);
+                if distance < tolerance && distance < last_distance {
+                    last_distance = distance;
+                    res = (Some(curves_index), Some(points_index));
+                }
+            }
+        }
+        res
+    }
+
+    //按下和松开的两个点围成的选框
+    fn marquee(from: Point, to: Point) -> IcedRectangle {
+        IcedRectangle::new(
+            Point::new(from.x.min(to.x), from.y.min(to.y)),
+            iced::Size::new((from.x - to.x).abs(), (from.y - to.y).abs()),
+        )
+    }
+
+    //选中的曲线整体的控制框，添加曲线的过程中不显示
+    fn gizmo(&self) -> Option<Gizmo> {
+        if !self.pending.borrow().shape.is_empty() {
+            return None;
+        }
+        self.selected
+            .borrow()
+            .curves
+            .iter()
+            .filter_map(|index| self.curves.get(*index))
+            .filter_map(|curve| curve.borrow().bounds())
+            .reduce(union)
+            .map(|bounds| Gizmo::new(bounds, self.viewport.borrow().zoom))
+    }
+
+    //吸附到网格和其余曲线的点上，skip里的曲线不参与，按住alt或者自由绘制时不吸附
+    fn snap(&self, state: &Interaction, point: Point, skip: &[usize]) -> (Point, Vec<Guide>) {
+        if state.alt_pressed || self.is_freehand() {
+            return (point, vec![]);
+        }
+
+        let mut targets = vec![];
+        if self.snap.to_points {
+            for (index, curve) in self.curves.iter().enumerate() {
+                let curve = curve.borrow();
+                let visible = self.layers.get(curve.layer).is_none_or(|l| l.visible);
+                if visible && !skip.contains(&index) {
+                    targets.extend(curve.snap_points());
+                }
+            }
+            //正在添加的曲线已经放下的点
+            targets.extend(self.pending.borrow().points().into_values());
+        }
+        self.snap.snap(point, &targets, self.tolerance())
+    }
+
+    //添加曲线时松开左键，点击放下一个点，点数不定的图形拖动时放下带控制点的节点，点数固定的图形不响应拖动
+    fn place(
+        &self,
+        state: &mut Interaction,
+        pressed: Point,
+        cursor_position: Point,
+    ) -> Option<EditMessage> {
+        let tolerance = self.tolerance();
+        let variable = self.is_variable();
+        if pressed.distance(cursor_position) < tolerance {
+            //双击结束点数不定的图形
+            if variable && state.double_clicked(cursor_position, tolerance) {
+                return Some(EditMessage::AddFromPending);
+            }
+            let (point, _) = self.snap(state, cursor_position, &[]);
+            Some(EditMessage::AddWithClick(point))
+        } else if variable {
+            let (from, _) = self.snap(state, pressed, &[]);
+            let (to, _) = self.snap(state, cursor_position, &[]);
+            Some(EditMessage::AddWithDrag(from, to))
+        } else {
+            None
+        }
+    }
+
+    fn is_path(&self) -> bool {
+        matches!(self.pending.borrow().shape, ShapeEnum::PathShape(_))
+    }
+
+    //点数不定的图形才能双击结束或者拖动放下节点
+    fn is_variable(&self) -> bool {
+        matches!(
+            self.pending.borrow().shape,
+            ShapeEnum::PathShape(_) | ShapeEnum::Polyline(_) | ShapeEnum::Polygon(_)
+        )
+    }
+
+    fn is_freehand(&self) -> bool {
+        matches!(self.pending.borrow().shape, ShapeEnum::Freehand(_))
+    }
+
+    fn redraw(&self) {
+        self.cache.borrow_mut().clear();
+    }
+}
+
+//叠放顺序的列表，最上面的曲线排在第一行，拖动一行可以改变顺序
+#[derive(Debug)]
+struct Stack<'a> {
+    curves: &'a [Rc<RefCell<Curve>>],
+    layers: &'a [Layer],
+    selected: Rc<RefCell<Selection>>,
+}
+
+impl<'a> Program<EditMessage> for Stack<'a> {
+    //拖动时拿起和放下的行
+    type State = Option<(usize, usize)>;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: Event,
+        bounds: IcedRectangle<f32>,
+        cursor: Cursor,
+    ) -> (event::Status, Option<EditMessage>) {
+        let len = self.curves.len();
+        let row = cursor
+            .position_in(&bounds)
+            .map(|position| ((position.y / Stack::ROW_HEIGHT) as usize).min(len.max(1) - 1));
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                if let (Some(row), true) = (row, len > 0) {
+                    *state = Some((row, row));
+                    return (event::Status::Captured, None);
+                }
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                if let (Some((_, to)), Some(row)) = (state.as_mut(), row) {
+                    *to = row;
+                    return (event::Status::Captured, None);
+                }
             }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if let Some((from, to)) = state.take() {
+                    let rows = Stack::rows(self.curves);
+                    let message = if from == to {
+                        EditMessage::Curve(CurveMessage::CurveSelected(rows[from]))
+                    } else {
+                        EditMessage::Reordered(rows[from], rows[to])
+                    };
+                    return (event::Status::Captured, Some(message));
+                }
+            }
+            _ => {}
         }
-
-        self.redraw();
         (event::Status::Ignored, None)
     }
 
@@ -359,33 +2386,54 @@ impl<'a> Program<EditMessage> for Pad<'a> {
         &self,
         state: &Self::State,
         bounds: IcedRectangle<f32>,
-        cursor: Cursor,
+        _cursor: Cursor,
     ) -> Vec<Geometry> {
-        let content = self
-            .cache
-            .borrow()
-            .draw(bounds.size(), |frame: &mut Frame| {
-                let selected = self.selected.borrow().0.clone();
-                self.curves.iter().enumerate().for_each(|(index, curve)| {
-                    curve.borrow().draw(frame, Some(index) == selected);
-                });
-
-                //现在curve to select是交互内的逻辑，因此绘制也放在这里
-                if let Some(curve_to_select) = &state.curve_to_select {
-                    curve_to_select.borrow().draw(frame, true);
-                }
+        let mut frame = Frame::new(bounds.size());
+        let highlight = Color::from_rgb(0.2, 0.5, 1.0);
+        let selected = self.selected.borrow();
 
-                frame.stroke(
-                    &Path::rectangle(Point::ORIGIN, frame.size()),
-                    Stroke::default(),
+        for (row, index) in Stack::rows(self.curves).into_iter().enumerate() {
+            let top = row as f32 * Stack::ROW_HEIGHT;
+            if selected.contains(index) {
+                frame.fill_rectangle(
+                    Point::new(0.0, top),
+                    iced::Size::new(bounds.width, Stack::ROW_HEIGHT),
+                    Color {
+                        a: 0.2,
+                        ..highlight
+                    },
                 );
+            }
 
-                if let Some(cursor_position) = cursor.position_in(&bounds) {
-                    self.pending.borrow().preview(frame, cursor_position);
-                }
+            let curve = self.curves[index].borrow();
+            let layer = self
+                .layers
+                .get(curve.layer)
+                .map_or("", |layer| layer.name.as_str());
+            frame.fill_text(CanvasText {
+                content: format!("{}: {}  ({})", index, curve.shape, layer),
+                position: Point::new(8.0, top + Stack::ROW_HEIGHT / 2.0),
+                vertical_alignment: Vertical::Center,
+                ..CanvasText::default()
             });
+        }
 
-        vec![content]
+        //拖动时在放下的位置画一条线
+        if let Some((from, to)) = *state {
+            if from != to {
+                let y = if to > from { to + 1 } else { to } as f32 * Stack::ROW_HEIGHT;
+                frame.stroke(
+                    &Path::line(Point::new(0.0, y), Point::new(bounds.width, y)),
+                    Stroke::default().with_width(2.0).with_color(highlight),
+                );
+            }
+        }
+
+        frame.stroke(
+            &Path::rectangle(Point::ORIGIN, frame.size()),
+            Stroke::default(),
+        );
+        vec![frame.into_geometry()]
     }
 
     fn mouse_interaction(
@@ -394,40 +2442,37 @@ impl<'a> Program<EditMessage> for Pad<'a> {
         bounds: IcedRectangle<f32>,
         cursor: Cursor,
     ) -> mouse::Interaction {
-        if cursor.is_over(&bounds) {
-            if self.selected.borrow().0.is_some() && state.ctrl_pressed {
-                mouse::Interaction::Grabbing
-            } else if state.curve_to_select.is_some() {
-                mouse::Interaction::Pointer
-            } else {
-                mouse::Interaction::Crosshair
-            }
+        if state.is_some() {
+            mouse::Interaction::Grabbing
+        } else if cursor.is_over(&bounds) && !self.curves.is_empty() {
+            mouse::Interaction::Grab
         } else {
             mouse::Interaction::default()
         }
     }
 }
 
-impl<'a> Pad<'a> {
-    const DETERMINANT_DISTANCE: f32 = 10.0;
+impl<'a> Stack<'a> {
+    const ROW_HEIGHT: f32 = 24.0;
 
-    fn decide_which_curve(&self, cursor_position: Point) -> (Option<usize>, Option<String>) {
-        let mut res = (None, None);
-        let mut last_distance = Pad::DETERMINANT_DISTANCE;
-        for (curves_index, curve) in self.curves.iter().enumerate() {
-            for (points_index, point) in curve.borrow().shape.points() {
-                let distance = point.distance(cursor_position);
-                if distance < Pad::DETERMINANT_DISTANCE && distance < last_distance {
-                    last_distance = distance;
-                    res = (Some(curves_index), Some(points_index));
-                }
-            }
-        }
-        res
+    //从上往下每一行的曲线下标，上面的图层排在前面，同一图层里后画的排在前面
+    fn rows(curves: &[Rc<RefCell<Curve>>]) -> Vec<usize> {
+        let mut rows = (0..curves.len()).collect::<Vec<_>>();
+        rows.sort_by_key(|index| std::cmp::Reverse((curves[*index].borrow().layer, *index)));
+        rows
     }
+}
 
-    fn redraw(&self) {
-        self.cache.borrow_mut().clear();
+//下标选择框里的一项，按叠放顺序排列
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StackItem {
+    index: usize,
+    name: String,
+}
+
+impl std::fmt::Display for StackItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.index, self.name)
     }
 }
 
@@ -435,64 +2480,92 @@ impl<'a> Pad<'a> {
 enum CurveLabel {
     Pending,
     Selected(usize),
+    //主要的曲线和选中的数量
+    Multiple(usize, usize),
+    Style,
 }
 
 impl CurveLabel {
-    fn text(&self) -> &str {
+    fn text(&self) -> String {
         match self {
-            CurveLabel::Pending => "Creating",
-            CurveLabel::Selected(_) => "Selected curve",
+            CurveLabel::Pending => "Creating".to_string(),
+            CurveLabel::Selected(_) => "Selected curve".to_string(),
+            CurveLabel::Multiple(_, count) => format!("{} curves selected", count),
+            CurveLabel::Style => "Editing style".to_string(),
         }
     }
 
     fn index(&self) -> Option<usize> {
         match self {
             CurveLabel::Pending => None,
-            CurveLabel::Selected(index) => Some(*index),
+            CurveLabel::Selected(index) | CurveLabel::Multiple(index, _) => Some(*index),
+            CurveLabel::Style => None,
         }
     }
 }
 
 #[derive(Debug)]
 struct Editable<'a> {
-    curves_len: usize,
+    //从最上面的曲线开始
+    stack: Vec<StackItem>,
     curve: &'a Rc<RefCell<Curve>>,
+    //多选时所有选中的曲线，用来判断数值是否一致
+    selection: Vec<&'a Rc<RefCell<Curve>>>,
     label: CurveLabel,
+    color_space: ColorSpace,
+    eyedropper: Option<ColorTarget>,
+    palette: Vec<Color>,
 }
 
 impl<'a> Editable<'a> {
+    const MIXED: &'static str = "mixed";
+
     fn view(self) -> Element<'a, CurveMessage> {
         let (
             points,
             attrs,
             Curve {
+                shape,
                 color,
                 width,
-                line_cap,
                 line_join,
-                // segments,
-                // offset,
+                fill,
+                stroke_gradient,
+                fill_gradient,
+                segments,
+                segments_input,
+                offset_input,
+                hex_input,
+                attribute_input,
+                non_scaling_stroke,
+                opacity,
+                shadow,
                 ..
             },
         ) = (
             self.curve.borrow().shape.points(),
             self.curve.borrow().shape.attributes(),
-            *self.curve.borrow(),
+            self.curve.borrow().clone(),
         );
 
-        let (r, g, b, a) = (
-            (color.r * 255.0).to_string(),
-            (color.g * 255.0).to_string(),
-            (color.b * 255.0).to_string(),
-            (color.a).to_string(),
-        );
-        let width = width.to_string();
+        //样式里没有渐变和填充规则
+        let styling = matches!(self.label, CurveLabel::Style);
+        //多选时只能修改样式，不显示各个图形自己的点和属性
+        let multiple = matches!(self.label, CurveLabel::Multiple(..));
+        let preset = self
+            .common(|c| c.segments.clone())
+            .and(DashPreset::matching(&segments, width));
+        let width = self.common(|c| c.width).map(|w| w.to_string());
 
         //排序points防止顺序一直变化
         let mut points = points.into_iter().collect::<Vec<(String, Point)>>();
         points.sort_by(|(a, _), (b, _)| a.cmp(b));
         let mut attrs = attrs.into_iter().collect::<Vec<(String, f32)>>();
         attrs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if multiple {
+            points.clear();
+            attrs.clear();
+        }
 
         let mut editable = Column::new()
             .width(Length::FillPortion(2))
@@ -511,9 +2584,12 @@ impl<'a> Editable<'a> {
                 .push(Text::new("Index:"))
                 .push(
                     PickList::new(
-                        (0..self.curves_len).collect::<Vec<usize>>(),
-                        self.label.index(),
-                        CurveMessage::CurveSelected,
+                        self.stack.clone(),
+                        self.label
+                            .index()
+                            .and_then(|index| self.stack.iter().find(|s| s.index == index))
+                            .cloned(),
+                        |item| CurveMessage::CurveSelected(item.index),
                     )
                     .style(style::PickList),
                 ),
@@ -528,68 +2604,169 @@ impl<'a> Editable<'a> {
             )
         });
 
+        //改不了的属性只显示数值
         editable = attrs.into_iter().fold(editable, |acc, (index, attr)| {
-            acc.push(
-                Row::new()
-                    .align_items(Alignment::Center)
-                    .spacing(10)
-                    .push(Text::new(format!("{:?}: {:.2}", index, attr))),
-            )
-        });
+            if !shape.is_editable(&index) {
+                return acc.push(
+                    Row::new()
+                        .align_items(Alignment::Center)
+                        .spacing(10)
+                        .push(Text::new(format!("{:?}: {:.2}", index, attr))),
+                );
+            }
 
-        editable
-            // .push(
-            //     Row::new()
-            //         .align_items(Alignment::Center)
-            //         .spacing(10)
-            //         .push(Text::new(format!("Segments: {:?}", segments)))
-            //         .push(Text::new(format!("offset: {:?}", offset))),
-            // )
-            .push(Text::new("Color:  "))
-            .push(
+            let value = match &attribute_input {
+                Some((name, input)) if *name == index => input.clone(),
+                _ => format_attribute(attr),
+            };
+            let name = index.clone();
+            acc.push(
                 Row::new()
                     .align_items(Alignment::Center)
                     .spacing(10)
-                    .push(Slider::new(0.0..=1.0, color.r, CurveMessage::SlideColorR).step(0.01))
+                    .push(Text::new(format!("{:?}: ", index)))
                     .push(
-                        text_input::TextInput::new("red", r.as_str(), CurveMessage::InputColorR)
-                            .style(style::TextInput::EditAttribute)
-                            .width(Length::Units(50)),
+                        text_input::TextInput::new(index.as_str(), value.as_str(), move |v| {
+                            CurveMessage::Shape(ShapeMessage::Attribute(name.clone(), v))
+                        })
+                        .style(style::TextInput::EditAttribute)
+                        .width(Length::Units(80)),
                     ),
             )
-            .push(
+        });
+
+        if let (ShapeEnum::Freehand(Freehand { tolerance, .. }), CurveLabel::Pending) =
+            (&shape, &self.label)
+        {
+            editable = editable.push(
                 Row::new()
                     .align_items(Alignment::Center)
                     .spacing(10)
-                    .push(Slider::new(0.0..=1.0, color.g, CurveMessage::SlideColorG).step(0.01))
+                    .push(Text::new("Tolerance:  "))
                     .push(
-                        text_input::TextInput::new("green", g.as_str(), CurveMessage::InputColorG)
-                            .style(style::TextInput::EditAttribute)
-                            .width(Length::Units(50)),
-                    ),
-            )
-            .push(
+                        Slider::new(Freehand::TOLERANCE_RANGE, *tolerance, |t| {
+                            CurveMessage::Shape(ShapeMessage::Tolerance(t))
+                        })
+                        .step(0.5),
+                    )
+                    .push(Text::new(format!("{:.1}", tolerance))),
+            );
+        }
+
+        if let (ShapeEnum::Arc(Arc { mode, .. }), false) = (&shape, multiple) {
+            editable = editable.push(
                 Row::new()
                     .align_items(Alignment::Center)
                     .spacing(10)
-                    .push(Slider::new(0.0..=1.0, color.b, CurveMessage::SlideColorB).step(0.01))
+                    .push(Text::new("Mode:  "))
                     .push(
-                        text_input::TextInput::new("blue", b.as_str(), CurveMessage::InputColorB)
-                            .style(style::TextInput::EditAttribute)
-                            .width(Length::Units(50)),
+                        PickList::new(ArcMode::ALL.to_vec(), Some(*mode), |m| {
+                            CurveMessage::Shape(ShapeMessage::ArcMode(m))
+                        })
+                        .style(style::PickList),
                     ),
-            )
-            .push(
-                Row::new()
-                    .align_items(Alignment::Center)
-                    .spacing(10)
-                    .push(Slider::new(0.0..=1.0, color.a, CurveMessage::SlideColorA).step(0.01))
-                    .push(
-                        text_input::TextInput::new("a", a.as_str(), CurveMessage::InputColorA)
+            );
+        }
+
+        if let (ShapeEnum::Arrow(Arrow { start, end, .. }), false) = (&shape, multiple) {
+            editable = editable
+                .push(
+                    Row::new()
+                        .align_items(Alignment::Center)
+                        .spacing(10)
+                        .push(Text::new("Start:  "))
+                        .push(
+                            PickList::new(Marker::ALL.to_vec(), Some(*start), |m| {
+                                CurveMessage::Shape(ShapeMessage::StartMarker(m))
+                            })
+                            .style(style::PickList),
+                        ),
+                )
+                .push(
+                    Row::new()
+                        .align_items(Alignment::Center)
+                        .spacing(10)
+                        .push(Text::new("End:  "))
+                        .push(
+                            PickList::new(Marker::ALL.to_vec(), Some(*end), |m| {
+                                CurveMessage::Shape(ShapeMessage::EndMarker(m))
+                            })
+                            .style(style::PickList),
+                        ),
+                );
+        }
+
+        if let (
+            ShapeEnum::Text(TextShape {
+                content,
+                size,
+                font,
+                align,
+                ..
+            }),
+            false,
+        ) = (shape, multiple)
+        {
+            editable = editable
+                .push(
+                    Row::new()
+                        .align_items(Alignment::Center)
+                        .spacing(10)
+                        .push(Text::new("Content:  "))
+                        .push(
+                            text_input::TextInput::new("text", content.as_str(), |c| {
+                                CurveMessage::Shape(ShapeMessage::TextContent(c))
+                            })
+                            .style(style::TextInput::EditAttribute),
+                        ),
+                )
+                .push(
+                    Row::new()
+                        .align_items(Alignment::Center)
+                        .spacing(10)
+                        .push(Text::new("Font Size:  "))
+                        .push(
+                            text_input::TextInput::new("size", size.to_string().as_str(), |s| {
+                                CurveMessage::Shape(ShapeMessage::TextSize(s))
+                            })
                             .style(style::TextInput::EditAttribute)
                             .width(Length::Units(50)),
-                    ),
-            )
+                        ),
+                )
+                .push(
+                    Row::new()
+                        .align_items(Alignment::Center)
+                        .spacing(10)
+                        .push(Text::new("Font:  "))
+                        .push(
+                            PickList::new(TextFont::ALL.to_vec(), Some(font), |f| {
+                                CurveMessage::Shape(ShapeMessage::TextFont(f))
+                            })
+                            .style(style::PickList),
+                        ),
+                )
+                .push(
+                    Row::new()
+                        .align_items(Alignment::Center)
+                        .spacing(10)
+                        .push(Text::new("Align:  "))
+                        .push(
+                            PickList::new(TextAlign::ALL.to_vec(), Some(align), |a| {
+                                CurveMessage::Shape(ShapeMessage::TextAlign(a))
+                            })
+                            .style(style::PickList),
+                        ),
+                );
+        }
+
+        editable = editable
+            .push(Text::new("Color:  "))
+            .push(self.color_picker(
+                ColorTarget::Stroke,
+                color,
+                &hex_input,
+                self.common(|c| c.color).is_none(),
+            ))
             .push(
                 Row::new()
                     .align_items(Alignment::Center)
@@ -597,8 +2774,8 @@ impl<'a> Editable<'a> {
                     .push(Text::new("Width:  "))
                     .push(
                         text_input::TextInput::new(
-                            width.as_str(),
-                            width.as_str(),
+                            width.as_deref().unwrap_or(Editable::MIXED),
+                            width.as_deref().unwrap_or_default(),
                             CurveMessage::InputWidth,
                         )
                         .style(style::TextInput::EditAttribute)
@@ -613,9 +2790,10 @@ impl<'a> Editable<'a> {
                     .push(
                         PickList::new(
                             vec![EqLineCap::Butt, EqLineCap::Round, EqLineCap::Square],
-                            Some(line_cap),
+                            self.common(|c| c.line_cap),
                             CurveMessage::LineCapSelected,
                         )
+                        .placeholder(Editable::MIXED)
                         .style(style::PickList),
                     ),
             )
@@ -627,12 +2805,542 @@ impl<'a> Editable<'a> {
                     .push(
                         PickList::new(
                             vec![EqLineJoin::Miter, EqLineJoin::Round, EqLineJoin::Bevel],
-                            Some(line_join),
+                            self.common(|c| c.line_join),
                             CurveMessage::LineJoinSelected,
                         )
+                        .placeholder(Editable::MIXED)
+                        .style(style::PickList),
+                    ),
+            );
+
+        //样式里没有这些描边选项
+        if !styling {
+            if line_join == EqLineJoin::Miter {
+                let miter_limit = self.common(|c| c.miter_limit).map(|m| m.to_string());
+                editable = editable.push(
+                    Row::new()
+                        .align_items(Alignment::Center)
+                        .spacing(10)
+                        .push(Text::new("Miter Limit:  "))
+                        .push(
+                            text_input::TextInput::new(
+                                miter_limit.as_deref().map_or(Editable::MIXED, |_| "4"),
+                                miter_limit.as_deref().unwrap_or_default(),
+                                CurveMessage::InputMiterLimit,
+                            )
+                            .style(style::TextInput::EditAttribute)
+                            .width(Length::Units(50)),
+                        ),
+                );
+                //虚线在画布上只能用固定的斜接限制
+                if !segments.is_empty() {
+                    editable = editable.push(Text::new(
+                        "Canvas preview of dashed strokes uses a fixed miter limit of 4",
+                    ));
+                }
+            }
+            editable = editable
+                .push(
+                    Row::new()
+                        .align_items(Alignment::Center)
+                        .spacing(10)
+                        .push(Text::new("Paint Order:  "))
+                        .push(
+                            PickList::new(
+                                PaintOrder::ALL.to_vec(),
+                                self.common(|c| c.paint_order),
+                                CurveMessage::PaintOrderSelected,
+                            )
+                            .placeholder(Editable::MIXED)
+                            .style(style::PickList),
+                        ),
+                )
+                .push(Checkbox::new(
+                    non_scaling_stroke,
+                    self.label("Non-scaling Stroke", |c| c.non_scaling_stroke),
+                    CurveMessage::NonScalingStrokeToggled,
+                ));
+        }
+
+        if !styling {
+            editable = editable.push(
+                Row::new()
+                    .align_items(Alignment::Center)
+                    .spacing(10)
+                    .push(Text::new("Stroke Paint:  "))
+                    .push(
+                        PickList::new(
+                            Paint::ALL.to_vec(),
+                            self.common(|c| {
+                                c.stroke_gradient
+                                    .as_ref()
+                                    .map_or(Paint::Flat, Gradient::paint)
+                            }),
+                            CurveMessage::StrokePaintSelected,
+                        )
+                        .placeholder(Editable::MIXED)
+                        .style(style::PickList),
+                    ),
+            );
+        }
+        if let Some(gradient) = &stroke_gradient {
+            editable = editable.push(gradient_stops(gradient, CurveMessage::StrokeGradient));
+        }
+
+        let segments_input = segments_input.unwrap_or_else(|| {
+            if preset.is_none() && self.common(|c| c.segments.clone()).is_none() {
+                return String::new();
+            }
+            segments
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        });
+        let offset = offset_input.or_else(|| self.common(|c| c.offset).map(|o| o.to_string()));
+
+        editable = editable
+            .push(
+                Row::new()
+                    .align_items(Alignment::Center)
+                    .spacing(10)
+                    .push(Text::new("Dash:  "))
+                    .push(
+                        PickList::new(
+                            DashPreset::ALL.to_vec(),
+                            preset,
+                            CurveMessage::DashPresetSelected,
+                        )
+                        .placeholder(if multiple { Editable::MIXED } else { "" })
                         .style(style::PickList),
                     ),
             )
-            .into()
+            .push(
+                Row::new()
+                    .align_items(Alignment::Center)
+                    .spacing(10)
+                    .push(Text::new("Segments:  "))
+                    .push(
+                        text_input::TextInput::new(
+                            if segments_input.is_empty() && multiple {
+                                Editable::MIXED
+                            } else {
+                                "4, 2"
+                            },
+                            segments_input.as_str(),
+                            CurveMessage::InputSegments,
+                        )
+                        .style(style::TextInput::EditAttribute)
+                        .width(Length::Units(120)),
+                    )
+                    .push(Text::new("Offset:  "))
+                    .push(
+                        text_input::TextInput::new(
+                            offset.as_deref().map_or(Editable::MIXED, |_| "0"),
+                            offset.as_deref().unwrap_or_default(),
+                            CurveMessage::InputOffset,
+                        )
+                        .style(style::TextInput::EditAttribute)
+                        .width(Length::Units(50)),
+                    ),
+            );
+
+        editable = editable.push(Checkbox::new(
+            fill.is_some(),
+            self.label("Fill", |c| c.fill.is_some()),
+            CurveMessage::FillToggled,
+        ));
+
+        if let Some(fill) = fill {
+            editable = editable.push(self.color_picker(
+                ColorTarget::Fill,
+                fill,
+                &hex_input,
+                self.common(|c| c.fill).is_none(),
+            ));
+            if !styling {
+                editable = editable
+                    .push(
+                        Row::new()
+                            .align_items(Alignment::Center)
+                            .spacing(10)
+                            .push(Text::new("Fill Paint:  "))
+                            .push(
+                                PickList::new(
+                                    Paint::ALL.to_vec(),
+                                    self.common(|c| {
+                                        c.fill_gradient
+                                            .as_ref()
+                                            .map_or(Paint::Flat, Gradient::paint)
+                                    }),
+                                    CurveMessage::FillPaintSelected,
+                                )
+                                .placeholder(Editable::MIXED)
+                                .style(style::PickList),
+                            ),
+                    )
+                    .push(
+                        Row::new()
+                            .align_items(Alignment::Center)
+                            .spacing(10)
+                            .push(Text::new("Fill Rule:  "))
+                            .push(
+                                PickList::new(
+                                    EqFillRule::ALL.to_vec(),
+                                    self.common(|c| c.fill_rule),
+                                    CurveMessage::FillRuleSelected,
+                                )
+                                .placeholder(Editable::MIXED)
+                                .style(style::PickList),
+                            ),
+                    );
+            }
+        }
+
+        if let Some(gradient) = &fill_gradient {
+            editable = editable.push(gradient_stops(gradient, CurveMessage::FillGradient));
+        }
+
+        if !styling {
+            editable = editable
+                .push(
+                    Row::new()
+                        .align_items(Alignment::Center)
+                        .spacing(10)
+                        .push(Text::new("Opacity:  "))
+                        .push(
+                            Slider::new(0.0..=1.0, opacity, CurveMessage::SlideOpacity)
+                                .on_release(CurveMessage::Released)
+                                .step(0.01),
+                        )
+                        .push(Text::new(
+                            self.common(|c| c.opacity)
+                                .map_or(Editable::MIXED.to_string(), |o| format!("{:.2}", o)),
+                        )),
+                )
+                .push(
+                    Row::new()
+                        .align_items(Alignment::Center)
+                        .spacing(10)
+                        .push(Text::new("Blend Mode:  "))
+                        .push(
+                            PickList::new(
+                                BlendMode::ALL.to_vec(),
+                                self.common(|c| c.blend_mode),
+                                CurveMessage::BlendModeSelected,
+                            )
+                            .placeholder(Editable::MIXED)
+                            .style(style::PickList),
+                        ),
+                )
+                .push(Checkbox::new(
+                    shadow.is_some(),
+                    self.label("Drop Shadow", |c| c.shadow.is_some()),
+                    CurveMessage::ShadowToggled,
+                ));
+
+            if let Some(shadow) = &shadow {
+                let input =
+                    |label: &str, value: Option<String>, message: fn(String) -> ShadowMessage| {
+                        Row::new()
+                            .align_items(Alignment::Center)
+                            .spacing(10)
+                            .push(Text::new(format!("{}:  ", label)))
+                            .push(
+                                text_input::TextInput::new(
+                                    value.as_ref().map_or(Editable::MIXED, |_| label),
+                                    value.as_deref().unwrap_or_default(),
+                                    move |v| CurveMessage::Shadow(message(v)),
+                                )
+                                .style(style::TextInput::EditAttribute)
+                                .width(Length::Units(80)),
+                            )
+                    };
+                let shadowed = |value: fn(&Shadow) -> f32| {
+                    self.common(|c| c.shadow.as_ref().map(value))
+                        .map(|_| value(shadow).to_string())
+                };
+                let color = match &shadow.color_input {
+                    Some(input) => Some(input.clone()),
+                    None => self
+                        .common(|c| c.shadow.as_ref().map(|s| s.color))
+                        .map(|_| get_format_color(shadow.color)),
+                };
+
+                editable = editable
+                    .push(input(
+                        "Offset X",
+                        shadowed(|s| s.dx),
+                        ShadowMessage::InputOffsetX,
+                    ))
+                    .push(input(
+                        "Offset Y",
+                        shadowed(|s| s.dy),
+                        ShadowMessage::InputOffsetY,
+                    ))
+                    .push(input(
+                        "Blur",
+                        shadowed(|s| s.blur),
+                        ShadowMessage::InputBlur,
+                    ))
+                    .push(input("Shadow Color", color, ShadowMessage::InputColor));
+            }
+        }
+
+        editable.into()
+    }
+
+    //所有选中的曲线都一样时才有值，否则显示为不确定
+    fn common<T: PartialEq>(&self, value: impl Fn(&Curve) -> T) -> Option<T> {
+        let current = value(&self.curve.borrow());
+        if self.selection.iter().any(|c| value(&c.borrow()) != current) {
+            None
+        } else {
+            Some(current)
+        }
+    }
+
+    //复选框没有不确定的状态，在文字后面注明
+    fn label(&self, text: &str, value: impl Fn(&Curve) -> bool) -> String {
+        match self.common(value) {
+            Some(_) => text.to_string(),
+            None => format!("{} ({})", text, Editable::MIXED),
+        }
+    }
+
+    //十六进制或颜色名的输入框、吸管、三种颜色空间的滑条和色板
+    fn color_picker(
+        &self,
+        target: ColorTarget,
+        color: Color,
+        hex_input: &Option<(ColorTarget, String)>,
+        mixed: bool,
+    ) -> Column<'a, CurveMessage> {
+        let hex = match hex_input {
+            Some((t, input)) if *t == target => input.clone(),
+            _ if mixed => String::new(),
+            _ => get_format_color(color),
+        };
+        let set = move |color: Color| CurveMessage::SetColor(target, color);
+
+        let mut picker = Column::new().spacing(10).push(
+            Row::new()
+                .align_items(Alignment::Center)
+                .spacing(10)
+                .push(
+                    text_input::TextInput::new(
+                        if mixed {
+                            Editable::MIXED
+                        } else {
+                            "#rrggbb[aa]"
+                        },
+                        hex.as_str(),
+                        move |h| CurveMessage::InputHex(target, h),
+                    )
+                    .style(style::TextInput::EditAttribute)
+                    .width(Length::Units(100)),
+                )
+                .push(
+                    Button::new(Text::new("Eyedropper"))
+                        .style(if self.eyedropper == Some(target) {
+                            style::Button::Confirm
+                        } else {
+                            style::Button::Toolbar
+                        })
+                        .on_press(CurveMessage::EyedropperToggled(target)),
+                )
+                .push(
+                    PickList::new(
+                        ColorSpace::ALL.to_vec(),
+                        Some(self.color_space),
+                        CurveMessage::ColorSpaceSelected,
+                    )
+                    .style(style::PickList),
+                ),
+        );
+
+        let slide: [fn(f32) -> CurveMessage; 4] = match target {
+            ColorTarget::Stroke => [
+                CurveMessage::SlideColorR,
+                CurveMessage::SlideColorG,
+                CurveMessage::SlideColorB,
+                CurveMessage::SlideColorA,
+            ],
+            ColorTarget::Fill => [
+                CurveMessage::SlideFillR,
+                CurveMessage::SlideFillG,
+                CurveMessage::SlideFillB,
+                CurveMessage::SlideFillA,
+            ],
+        };
+        let input: [fn(String) -> CurveMessage; 4] = match target {
+            ColorTarget::Stroke => [
+                CurveMessage::InputColorR,
+                CurveMessage::InputColorG,
+                CurveMessage::InputColorB,
+                CurveMessage::InputColorA,
+            ],
+            ColorTarget::Fill => [
+                CurveMessage::InputFillR,
+                CurveMessage::InputFillG,
+                CurveMessage::InputFillB,
+                CurveMessage::InputFillA,
+            ],
+        };
+
+        let a = color.a;
+        picker = match self.color_space {
+            ColorSpace::Rgb => picker
+                .push(channel("red", color.r, color.r * 255.0, slide[0], input[0]))
+                .push(channel(
+                    "green",
+                    color.g,
+                    color.g * 255.0,
+                    slide[1],
+                    input[1],
+                ))
+                .push(channel(
+                    "blue",
+                    color.b,
+                    color.b * 255.0,
+                    slide[2],
+                    input[2],
+                )),
+            ColorSpace::Hsl => {
+                let (h, s, l) = to_hsl(color);
+                picker
+                    .push(component("H", 0.0..=360.0, h, move |h| {
+                        set(from_hsl(h, s, l, a))
+                    }))
+                    .push(component("S", 0.0..=1.0, s, move |s| {
+                        set(from_hsl(h, s, l, a))
+                    }))
+                    .push(component("L", 0.0..=1.0, l, move |l| {
+                        set(from_hsl(h, s, l, a))
+                    }))
+            }
+            ColorSpace::Hsv => {
+                let (h, s, v) = to_hsv(color);
+                picker
+                    .push(component("H", 0.0..=360.0, h, move |h| {
+                        set(from_hsv(h, s, v, a))
+                    }))
+                    .push(component("S", 0.0..=1.0, s, move |s| {
+                        set(from_hsv(h, s, v, a))
+                    }))
+                    .push(component("V", 0.0..=1.0, v, move |v| {
+                        set(from_hsv(h, s, v, a))
+                    }))
+            }
+        };
+        picker = picker.push(channel("a", a, a, slide[3], input[3]));
+
+        if self.palette.is_empty() {
+            picker
+        } else {
+            picker.push(self.palette.iter().fold(
+                Row::new().align_items(Alignment::Center).spacing(5),
+                |acc, swatch| {
+                    acc.push(
+                        Button::new(Space::new(Length::Units(14), Length::Units(14)))
+                            .style(style::Button::Swatch(*swatch))
+                            .on_press(CurveMessage::SwatchPicked(target, *swatch)),
+                    )
+                },
+            ))
+        }
     }
 }
+
+//滑条加上可以直接输入的数值
+fn channel<'a>(
+    placeholder: &str,
+    value: f32,
+    text: f32,
+    slide: fn(f32) -> CurveMessage,
+    input: fn(String) -> CurveMessage,
+) -> Row<'a, CurveMessage> {
+    Row::new()
+        .align_items(Alignment::Center)
+        .spacing(10)
+        .push(
+            Slider::new(0.0..=1.0, value, slide)
+                .on_release(CurveMessage::Released)
+                .step(0.01),
+        )
+        .push(
+            text_input::TextInput::new(placeholder, text.to_string().as_str(), input)
+                .style(style::TextInput::EditAttribute)
+                .width(Length::Units(50)),
+        )
+}
+
+//HSL和HSV的分量只用滑条调整
+fn component<'a>(
+    label: &str,
+    range: std::ops::RangeInclusive<f32>,
+    value: f32,
+    message: impl Fn(f32) -> CurveMessage + 'a,
+) -> Row<'a, CurveMessage> {
+    let step = (range.end() - range.start()) / 100.0;
+    Row::new()
+        .align_items(Alignment::Center)
+        .spacing(10)
+        .push(Text::new(format!("{}:", label)))
+        .push(
+            Slider::new(range, value, message)
+                .on_release(CurveMessage::Released)
+                .step(step),
+        )
+        .push(Text::new(format!("{:.2}", value)))
+}
+
+//每个色标一行，位置用滑条，颜色用十六进制输入
+fn gradient_stops<'a>(
+    gradient: &Gradient,
+    message: fn(GradientMessage) -> CurveMessage,
+) -> Column<'a, CurveMessage> {
+    let stops =
+        gradient
+            .stops
+            .iter()
+            .enumerate()
+            .fold(Column::new().spacing(10), |acc, (index, stop)| {
+                let color_input = match &gradient.color_input {
+                    Some((i, input)) if *i == index => input.clone(),
+                    _ => get_format_color(stop.color),
+                };
+                acc.push(
+                    Row::new()
+                        .align_items(Alignment::Center)
+                        .spacing(10)
+                        .push(Text::new(format!("Stop {}:", index)))
+                        .push(
+                            Slider::new(0.0..=1.0, stop.offset, move |o| {
+                                message(GradientMessage::StopOffset(index, o))
+                            })
+                            .on_release(CurveMessage::Released)
+                            .step(0.01),
+                        )
+                        .push(Text::new(format!("{:.2}", stop.offset)))
+                        .push(
+                            text_input::TextInput::new("#000000", color_input.as_str(), move |c| {
+                                message(GradientMessage::StopColor(index, c))
+                            })
+                            .style(style::TextInput::EditAttribute)
+                            .width(Length::Units(80)),
+                        )
+                        .push(
+                            Button::new(Text::new("-"))
+                                .style(style::Button::Delete)
+                                .on_press(message(GradientMessage::RemoveStop(index))),
+                        ),
+                )
+            });
+
+    stops.push(
+        Button::new(Text::new("Add Stop"))
+            .style(style::Button::Confirm)
+            .on_press(message(GradientMessage::AddStop)),
+    )
+}
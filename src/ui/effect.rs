@@ -0,0 +1,211 @@
+use std::fmt::Display;
+
+use iced::{Color, Vector};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use svg::node::element::{Element, Filter};
+use svg::Node;
+
+use crate::utils::{get_format_rgb, parse_color, SerdeColor};
+
+#[derive(Debug, Clone)]
+pub enum ShadowMessage {
+    InputOffsetX(String),
+    InputOffsetY(String),
+    InputBlur(String),
+    InputColor(String),
+}
+
+//和css的mix-blend-mode对应，画布上没法混合，只在导出时生效
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 16] = [
+        BlendMode::Normal,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Overlay,
+        BlendMode::Darken,
+        BlendMode::Lighten,
+        BlendMode::ColorDodge,
+        BlendMode::ColorBurn,
+        BlendMode::HardLight,
+        BlendMode::SoftLight,
+        BlendMode::Difference,
+        BlendMode::Exclusion,
+        BlendMode::Hue,
+        BlendMode::Saturation,
+        BlendMode::Color,
+        BlendMode::Luminosity,
+    ];
+}
+
+impl Display for BlendMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                BlendMode::Normal => "normal",
+                BlendMode::Multiply => "multiply",
+                BlendMode::Screen => "screen",
+                BlendMode::Overlay => "overlay",
+                BlendMode::Darken => "darken",
+                BlendMode::Lighten => "lighten",
+                BlendMode::ColorDodge => "color-dodge",
+                BlendMode::ColorBurn => "color-burn",
+                BlendMode::HardLight => "hard-light",
+                BlendMode::SoftLight => "soft-light",
+                BlendMode::Difference => "difference",
+                BlendMode::Exclusion => "exclusion",
+                BlendMode::Hue => "hue",
+                BlendMode::Saturation => "saturation",
+                BlendMode::Color => "color",
+                BlendMode::Luminosity => "luminosity",
+            }
+        )
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shadow {
+    pub dx: f32,
+    pub dy: f32,
+    //模糊半径，导出时标准差取它的一半
+    pub blur: f32,
+    #[serde_as(as = "SerdeColor")]
+    pub color: Color,
+    //正在输入的颜色，输入到一半时无法解析
+    #[serde(skip)]
+    pub color_input: Option<String>,
+}
+
+impl Default for Shadow {
+    fn default() -> Self {
+        Shadow {
+            dx: 4.0,
+            dy: 4.0,
+            blur: 4.0,
+            color: Color::from_rgba(0.0, 0.0, 0.0, 0.5),
+            color_input: None,
+        }
+    }
+}
+
+impl Shadow {
+    //画布上模糊用几层逐渐变宽的描边近似
+    pub const LAYERS: usize = 4;
+
+    pub fn offset(&self) -> Vector {
+        Vector::new(self.dx, self.dy)
+    }
+
+    pub fn update(&mut self, message: ShadowMessage) {
+        if !matches!(message, ShadowMessage::InputColor(_)) {
+            self.color_input = None;
+        }
+
+        match message {
+            ShadowMessage::InputOffsetX(dx) => {
+                if let Ok(dx) = dx.parse::<f32>() {
+                    self.dx = dx;
+                }
+            }
+            ShadowMessage::InputOffsetY(dy) => {
+                if let Ok(dy) = dy.parse::<f32>() {
+                    self.dy = dy;
+                }
+            }
+            ShadowMessage::InputBlur(blur) => {
+                if let Ok(blur) = blur.parse::<f32>() {
+                    if blur >= 0.0 {
+                        self.blur = blur;
+                    }
+                }
+            }
+            ShadowMessage::InputColor(input) => {
+                if let Some(color) = parse_color(&input) {
+                    self.color = color;
+                }
+                self.color_input = Some(input);
+            }
+        }
+    }
+
+    //模糊透明度、偏移、上色，再叠在原图形下面
+    pub fn export_as_svg(&self, id: &str) -> Filter {
+        let primitive = |name: &str, attributes: &[(&str, String)]| {
+            let mut element = Element::new(name);
+            for (key, value) in attributes {
+                element.assign(*key, value.as_str());
+            }
+            element
+        };
+
+        let mut merge = Element::new("feMerge");
+        merge.append(primitive("feMergeNode", &[("in", "shadow".into())]));
+        merge.append(primitive("feMergeNode", &[("in", "SourceGraphic".into())]));
+
+        Filter::new()
+            .set("id", id)
+            .set("x", "-50%")
+            .set("y", "-50%")
+            .set("width", "200%")
+            .set("height", "200%")
+            .add(primitive(
+                "feGaussianBlur",
+                &[
+                    ("in", "SourceAlpha".into()),
+                    ("stdDeviation", (self.blur / 2.0).to_string()),
+                    ("result", "blur".into()),
+                ],
+            ))
+            .add(primitive(
+                "feOffset",
+                &[
+                    ("in", "blur".into()),
+                    ("dx", self.dx.to_string()),
+                    ("dy", self.dy.to_string()),
+                    ("result", "offset".into()),
+                ],
+            ))
+            .add(primitive(
+                "feFlood",
+                &[
+                    ("flood-color", get_format_rgb(self.color)),
+                    ("flood-opacity", self.color.a.to_string()),
+                    ("result", "color".into()),
+                ],
+            ))
+            .add(primitive(
+                "feComposite",
+                &[
+                    ("in", "color".into()),
+                    ("in2", "offset".into()),
+                    ("operator", "in".into()),
+                    ("result", "shadow".into()),
+                ],
+            ))
+            .add(merge)
+    }
+}
@@ -0,0 +1,132 @@
+use std::path::PathBuf;
+
+use iced::pure::widget::image::{Handle, Image};
+use iced::{Length, Renderer};
+use iced_native::image::Renderer as _;
+use iced_native::layout::{self, Layout};
+use iced_native::{
+    event, mouse, renderer, Clipboard, ContentFit, Event, Point, Rectangle, Shell, Size,
+};
+use iced_pure::widget::{Tree, Widget};
+use iced_pure::Element;
+
+//显示图片，点击时给出点到的是图片上的哪个像素
+pub struct Eyedropper<'a, Message> {
+    handle: Handle,
+    image: Image,
+    on_pick: Box<dyn Fn(u32, u32) -> Message + 'a>,
+}
+
+impl<'a, Message> Eyedropper<'a, Message> {
+    pub fn new(path: impl Into<PathBuf>, on_pick: impl Fn(u32, u32) -> Message + 'a) -> Self {
+        let handle = Handle::from_path(path);
+        Eyedropper {
+            image: Image::new(handle.clone()),
+            handle,
+            on_pick: Box::new(on_pick),
+        }
+    }
+
+    pub fn height(mut self, height: Length) -> Self {
+        self.image = self.image.height(height);
+        self
+    }
+
+    //和Image::draw里的计算方式一致：按比例缩放到边界以内并居中
+    fn pixel(
+        &self,
+        renderer: &Renderer,
+        bounds: Rectangle,
+        cursor_position: Point,
+    ) -> Option<(u32, u32)> {
+        let (width, height) = renderer.dimensions(&self.handle);
+        let fitted = ContentFit::Contain.fit(Size::new(width as f32, height as f32), bounds.size());
+        let x = (cursor_position.x - bounds.x - (bounds.width - fitted.width).max(0.0) / 2.0)
+            / fitted.width;
+        let y = (cursor_position.y - bounds.y - (bounds.height - fitted.height).max(0.0) / 2.0)
+            / fitted.height;
+
+        if (0.0..1.0).contains(&x) && (0.0..1.0).contains(&y) {
+            Some(((x * width as f32) as u32, (y * height as f32) as u32))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, Message> Widget<Message, Renderer> for Eyedropper<'a, Message> {
+    fn width(&self) -> Length {
+        <Image as Widget<Message, Renderer>>::width(&self.image)
+    }
+
+    fn height(&self) -> Length {
+        <Image as Widget<Message, Renderer>>::height(&self.image)
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        <Image as Widget<Message, Renderer>>::layout(&self.image, renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        state: &Tree,
+        renderer: &mut Renderer,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) {
+        <Image as Widget<Message, Renderer>>::draw(
+            &self.image,
+            state,
+            renderer,
+            style,
+            layout,
+            cursor_position,
+            viewport,
+        )
+    }
+
+    fn on_event(
+        &mut self,
+        _state: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        _clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+    ) -> event::Status {
+        if let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event {
+            if let Some((x, y)) = self.pixel(renderer, layout.bounds(), cursor_position) {
+                shell.publish((self.on_pick)(x, y));
+                return event::Status::Captured;
+            }
+        }
+        event::Status::Ignored
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &Tree,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        if self
+            .pixel(renderer, layout.bounds(), cursor_position)
+            .is_some()
+        {
+            mouse::Interaction::Crosshair
+        } else {
+            mouse::Interaction::Idle
+        }
+    }
+}
+
+impl<'a, Message: 'a> From<Eyedropper<'a, Message>> for Element<'a, Message, Renderer> {
+    fn from(eyedropper: Eyedropper<'a, Message>) -> Self {
+        Element::new(eyedropper)
+    }
+}
@@ -0,0 +1,327 @@
+use std::fmt::Display;
+
+use iced::{Color, Point, Vector};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use svg::node::element::{Element, LinearGradient, RadialGradient, Stop};
+
+use super::transform::Transform;
+use crate::utils::{get_format_rgb, get_radius, parse_color, SerdeColor, SerdePoint};
+
+#[derive(Debug, Clone)]
+pub enum GradientMessage {
+    StopOffset(usize, f32),
+    StopColor(usize, String),
+    AddStop,
+    RemoveStop(usize),
+}
+
+//描边和填充可以选择的颜料
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Paint {
+    Flat,
+    Linear,
+    Radial,
+}
+
+impl Paint {
+    pub const ALL: [Paint; 3] = [Paint::Flat, Paint::Linear, Paint::Radial];
+}
+
+impl Display for Paint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Paint::Flat => "flat",
+                Paint::Linear => "linear gradient",
+                Paint::Radial => "radial gradient",
+            }
+        )
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GradientStop {
+    //0到1之间
+    pub offset: f32,
+    #[serde_as(as = "SerdeColor")]
+    pub color: Color,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum GradientKind {
+    Linear {
+        #[serde_as(as = "SerdePoint")]
+        start: Point,
+        #[serde_as(as = "SerdePoint")]
+        end: Point,
+    },
+    Radial {
+        #[serde_as(as = "SerdePoint")]
+        center: Point,
+        radius: f32,
+        #[serde_as(as = "SerdePoint")]
+        focal: Point,
+    },
+}
+
+//坐标都是画布上的绝对坐标，导出时用userSpaceOnUse
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    pub stops: Vec<GradientStop>,
+    //正在输入的颜色，输入到一半时无法解析
+    #[serde(skip)]
+    pub color_input: Option<(usize, String)>,
+}
+
+impl Gradient {
+    //min和max是图形的包围盒，渐变默认铺满整个图形
+    pub fn new(paint: Paint, color: Color, min: Point, max: Point) -> Option<Gradient> {
+        let center = Point::new((min.x + max.x) / 2.0, (min.y + max.y) / 2.0);
+        let kind = match paint {
+            Paint::Flat => return None,
+            Paint::Linear => GradientKind::Linear {
+                start: Point::new(min.x, center.y),
+                end: Point::new(max.x, center.y),
+            },
+            Paint::Radial => GradientKind::Radial {
+                center,
+                radius: get_radius(center, max).max(1.0),
+                focal: center,
+            },
+        };
+
+        Some(Gradient {
+            kind,
+            stops: vec![
+                GradientStop { offset: 0.0, color },
+                GradientStop {
+                    offset: 1.0,
+                    color: Color::WHITE,
+                },
+            ],
+            color_input: None,
+        })
+    }
+
+    pub fn paint(&self) -> Paint {
+        match self.kind {
+            GradientKind::Linear { .. } => Paint::Linear,
+            GradientKind::Radial { .. } => Paint::Radial,
+        }
+    }
+
+    //画布上可以拖动的点
+    pub fn handles(&self) -> Vec<(&'static str, Point)> {
+        match self.kind {
+            GradientKind::Linear { start, end } => vec![("start", start), ("end", end)],
+            GradientKind::Radial {
+                center,
+                radius,
+                focal,
+            } => vec![
+                ("center", center),
+                ("radius", center + Vector::new(radius, 0.0)),
+                ("focal", focal),
+            ],
+        }
+    }
+
+    pub fn move_handle(&mut self, handle: &str, point: Point) {
+        match (&mut self.kind, handle) {
+            (GradientKind::Linear { start, .. }, "start") => *start = point,
+            (GradientKind::Linear { end, .. }, "end") => *end = point,
+            //拖动圆心时焦点跟着移动
+            (GradientKind::Radial { center, focal, .. }, "center") => {
+                *focal = *focal + (point - *center);
+                *center = point;
+            }
+            (GradientKind::Radial { center, radius, .. }, "radius") => {
+                *radius = get_radius(*center, point).max(1.0);
+            }
+            (GradientKind::Radial { focal, .. }, "focal") => *focal = point,
+            _ => {}
+        }
+    }
+
+    pub fn translate(&mut self, offset: Vector) {
+        match &mut self.kind {
+            GradientKind::Linear { start, end } => {
+                *start = *start + offset;
+                *end = *end + offset;
+            }
+            GradientKind::Radial { center, focal, .. } => {
+                *center = *center + offset;
+                *focal = *focal + offset;
+            }
+        }
+    }
+
+    //径向渐变只能是圆，半径按平均的缩放比例变化
+    pub fn transform(&mut self, transform: &Transform) {
+        match &mut self.kind {
+            GradientKind::Linear { start, end } => {
+                *start = transform.apply(*start);
+                *end = transform.apply(*end);
+            }
+            GradientKind::Radial {
+                center,
+                radius,
+                focal,
+            } => {
+                *center = transform.apply(*center);
+                *focal = transform.apply(*focal);
+                *radius = (*radius * transform.scale_factor()).max(1.0);
+            }
+        }
+    }
+
+    pub fn update(&mut self, message: GradientMessage) {
+        match message {
+            GradientMessage::StopOffset(index, offset) => {
+                if let Some(stop) = self.stops.get_mut(index) {
+                    stop.offset = offset.clamp(0.0, 1.0);
+                }
+            }
+            GradientMessage::StopColor(index, input) => {
+                if let (Some(stop), Some(color)) = (self.stops.get_mut(index), parse_color(&input))
+                {
+                    stop.color = color;
+                }
+                self.color_input = Some((index, input));
+            }
+            GradientMessage::AddStop => {
+                let color = self.stops.last().map(|s| s.color).unwrap_or(Color::BLACK);
+                self.stops.push(GradientStop { offset: 1.0, color });
+                self.color_input = None;
+            }
+            //至少保留两个色标
+            GradientMessage::RemoveStop(index) => {
+                if self.stops.len() > 2 && index < self.stops.len() {
+                    self.stops.remove(index);
+                    self.color_input = None;
+                }
+            }
+        }
+    }
+
+    fn sorted_stops(&self) -> Vec<GradientStop> {
+        let mut stops = self.stops.clone();
+        stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        stops
+    }
+
+    //画布不支持渐变，用整个渐变的平均颜色近似
+    pub fn average(&self) -> Color {
+        let stops = self.sorted_stops();
+        let (first, last) = match (stops.first(), stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Color::BLACK,
+        };
+
+        let mut sum = [0.0; 4];
+        let mut add = |color: Color, weight: f32| {
+            for (s, c) in sum.iter_mut().zip(color.into_linear()) {
+                *s += c * weight;
+            }
+        };
+
+        add(first.color, first.offset);
+        add(last.color, 1.0 - last.offset);
+        for pair in stops.windows(2) {
+            let weight = (pair[1].offset - pair[0].offset) / 2.0;
+            add(pair[0].color, weight);
+            add(pair[1].color, weight);
+        }
+
+        //线性空间里求平均，再换回sRGB
+        let encode = |c: f32| {
+            if c <= 0.003_130_8 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            }
+        };
+        Color::from_rgba(encode(sum[0]), encode(sum[1]), encode(sum[2]), sum[3])
+    }
+
+    pub fn export_as_svg(&self, id: &str) -> Element {
+        let stops = self.sorted_stops().into_iter().map(|stop| {
+            Stop::new()
+                .set("offset", stop.offset)
+                .set("stop-color", get_format_rgb(stop.color))
+                .set("stop-opacity", stop.color.a)
+        });
+
+        match self.kind {
+            GradientKind::Linear { start, end } => stops
+                .fold(
+                    LinearGradient::new()
+                        .set("id", id)
+                        .set("gradientUnits", "userSpaceOnUse")
+                        .set("x1", start.x)
+                        .set("y1", start.y)
+                        .set("x2", end.x)
+                        .set("y2", end.y),
+                    |acc, stop| acc.add(stop),
+                )
+                .into(),
+            GradientKind::Radial {
+                center,
+                radius,
+                focal,
+            } => stops
+                .fold(
+                    RadialGradient::new()
+                        .set("id", id)
+                        .set("gradientUnits", "userSpaceOnUse")
+                        .set("cx", center.x)
+                        .set("cy", center.y)
+                        .set("r", radius)
+                        .set("fx", focal.x)
+                        .set("fy", focal.y),
+                    |acc, stop| acc.add(stop),
+                )
+                .into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_of_black_to_white_is_light_gray() {
+        let gradient = Gradient::new(
+            Paint::Linear,
+            Color::BLACK,
+            Point::ORIGIN,
+            Point::new(10.0, 10.0),
+        )
+        .unwrap();
+        let average = gradient.average();
+        //线性空间的0.5换回sRGB大约是0.735
+        for c in [average.r, average.g, average.b] {
+            assert!((c - 0.735).abs() < 0.01, "{}", c);
+        }
+        assert_eq!(average.a, 1.0);
+    }
+
+    #[test]
+    fn average_of_a_single_color_is_that_color() {
+        let color = Color::from_rgb(0.2, 0.6, 0.9);
+        let mut gradient =
+            Gradient::new(Paint::Radial, color, Point::ORIGIN, Point::new(4.0, 4.0)).unwrap();
+        gradient.stops[1].color = color;
+        let average = gradient.average();
+        for (a, b) in [(average.r, 0.2), (average.g, 0.6), (average.b, 0.9)] {
+            assert!((a - b).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use svg::node::element::{Element, Group as SvgGroup};
+use svg::Node;
+
+//曲线所在的组，从最外层到最里层的组编号，为空时不在任何组里
+//编号相同的曲线属于同一个组，组的嵌套由编号的先后表示
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Group(Vec<usize>);
+
+impl Group {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn ids(&self) -> &[usize] {
+        &self.0
+    }
+
+    //选中和移动时以最外层的组为单位
+    pub fn outermost(&self) -> Option<usize> {
+        self.0.first().copied()
+    }
+
+    //套进一个新的最外层的组
+    pub fn wrap(&mut self, id: usize) {
+        self.0.insert(0, id);
+    }
+
+    //解散最外层的组，里面嵌套的组保留
+    pub fn dissolve(&mut self) -> bool {
+        if self.0.is_empty() {
+            false
+        } else {
+            self.0.remove(0);
+            true
+        }
+    }
+
+    //粘贴出来的曲线换成新的编号，不会并进原来的组
+    pub fn renumber(&mut self, ids: &mut HashMap<usize, usize>, next: &mut usize) {
+        for id in self.0.iter_mut() {
+            *id = *ids.entry(*id).or_insert_with(|| {
+                *next += 1;
+                *next - 1
+            });
+        }
+    }
+
+    //按组的编号把曲线套进嵌套的<g>，编辑时同一个组的曲线总是挨在一起，每个组只导出一个<g>
+    pub fn export<T: Node>(root: &mut T, elements: impl IntoIterator<Item = (Group, Element)>) {
+        let mut opened: Vec<(usize, SvgGroup)> = vec![];
+        let close = |opened: &mut Vec<(usize, SvgGroup)>, root: &mut T| {
+            if let Some((_, group)) = opened.pop() {
+                match opened.last_mut() {
+                    Some((_, parent)) => parent.append(group),
+                    None => root.append(group),
+                }
+            }
+        };
+
+        for (Group(ids), element) in elements {
+            let common = opened
+                .iter()
+                .zip(&ids)
+                .take_while(|((opened, _), id)| opened == *id)
+                .count();
+            while opened.len() > common {
+                close(&mut opened, root);
+            }
+            for id in &ids[common..] {
+                opened.push((*id, SvgGroup::new()));
+            }
+
+            match opened.last_mut() {
+                Some((_, group)) => group.append(element),
+                None => root.append(element),
+            }
+        }
+
+        while !opened.is_empty() {
+            close(&mut opened, root);
+        }
+    }
+}
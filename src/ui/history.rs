@@ -0,0 +1,455 @@
+use std::collections::VecDeque;
+use std::mem::{discriminant, Discriminant};
+
+use super::curve::{Curve, CurveMessage};
+use super::effect::ShadowMessage;
+use super::gradient::GradientMessage;
+use super::layer::{Layer, LayerMessage};
+use super::named_style::NamedStyle;
+use super::shape::ShapeMessage;
+
+//整个文档，清空和修改共用样式这类操作直接记录前后的文档
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    pub curves: Vec<Curve>,
+    pub styles: Vec<NamedStyle>,
+    pub layers: Vec<Layer>,
+}
+
+//可以撤销的操作，记录下操作前后的曲线
+#[derive(Debug, Clone)]
+pub enum EditCommand {
+    Insert {
+        index: usize,
+        curve: Curve,
+    },
+    Remove {
+        index: usize,
+        curve: Curve,
+    },
+    Modify {
+        index: usize,
+        before: Box<Curve>,
+        after: Box<Curve>,
+    },
+    Replace {
+        before: Snapshot,
+        after: Snapshot,
+    },
+    //修改共用样式本身，引用它的曲线另外用Modify记录
+    Style {
+        index: usize,
+        before: NamedStyle,
+        after: NamedStyle,
+    },
+    //多选、编辑共用样式和变换时一次修改几条曲线，只记录改到的曲线
+    Batch(Vec<EditCommand>),
+}
+
+impl EditCommand {
+    //修改的是同一条曲线或者同一个样式才能合并，Batch还要求每一项都对得上
+    fn can_merge(&self, next: &EditCommand) -> bool {
+        match (self, next) {
+            (EditCommand::Modify { index, .. }, EditCommand::Modify { index: next, .. })
+            | (EditCommand::Style { index, .. }, EditCommand::Style { index: next, .. }) => {
+                index == next
+            }
+            (EditCommand::Replace { .. }, EditCommand::Replace { .. }) => true,
+            (EditCommand::Batch(commands), EditCommand::Batch(next)) => {
+                commands.len() == next.len()
+                    && commands
+                        .iter()
+                        .zip(next)
+                        .all(|(command, next)| command.can_merge(next))
+            }
+            _ => false,
+        }
+    }
+
+    //连续的同类修改合并成一次，只保留最早的before和最新的after，调用前先用can_merge判断
+    fn merge(&mut self, next: EditCommand) {
+        match (self, next) {
+            (EditCommand::Modify { after, .. }, EditCommand::Modify { after: next, .. }) => {
+                *after = next;
+            }
+            (EditCommand::Replace { after, .. }, EditCommand::Replace { after: next, .. }) => {
+                *after = next;
+            }
+            (EditCommand::Style { after, .. }, EditCommand::Style { after: next, .. }) => {
+                *after = next;
+            }
+            (EditCommand::Batch(commands), EditCommand::Batch(next)) => {
+                for (command, next) in commands.iter_mut().zip(next) {
+                    command.merge(next);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+//拖动滑条和拖动点时会连续发出同一种消息，用它判断能否合并，松开鼠标时结束合并
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeKey {
+    //None表示记录整个文档的修改，比如编辑共用样式和多选
+    target: Option<usize>,
+    //None表示修改样式名或者图层
+    message: Option<Discriminant<CurveMessage>>,
+    shape: Option<Discriminant<ShapeMessage>>,
+    //拖动的点或者修改的属性
+    handle: Option<String>,
+    //修改图层的名字和透明度
+    layer: Option<Discriminant<LayerMessage>>,
+}
+
+impl MergeKey {
+    //只有滑条、输入框和拖动这类连续的修改才能合并，切换和选择每次都单独记录
+    pub fn new(target: Option<usize>, message: &CurveMessage) -> Option<Self> {
+        let (shape, handle) = match message {
+            CurveMessage::Shape(sm) => (
+                Some(discriminant(sm)),
+                match sm {
+                    ShapeMessage::MovePoint(handle, _)
+                    | ShapeMessage::Move(handle, _)
+                    | ShapeMessage::Attribute(handle, _) => Some(handle.clone()),
+                    ShapeMessage::TextContent(_)
+                    | ShapeMessage::TextSize(_)
+                    | ShapeMessage::Tolerance(_) => None,
+                    _ => return None,
+                },
+            ),
+            CurveMessage::StrokeGradient(gm) | CurveMessage::FillGradient(gm) => (
+                None,
+                match gm {
+                    GradientMessage::StopOffset(index, _) => Some(format!("offset {}", index)),
+                    GradientMessage::StopColor(index, _) => Some(format!("color {}", index)),
+                    _ => return None,
+                },
+            ),
+            CurveMessage::Shadow(sm) => (
+                None,
+                Some(String::from(match sm {
+                    ShadowMessage::InputOffsetX(_) => "dx",
+                    ShadowMessage::InputOffsetY(_) => "dy",
+                    ShadowMessage::InputBlur(_) => "blur",
+                    ShadowMessage::InputColor(_) => "color",
+                })),
+            ),
+            CurveMessage::InputColorR(_)
+            | CurveMessage::InputColorG(_)
+            | CurveMessage::InputColorB(_)
+            | CurveMessage::InputColorA(_)
+            | CurveMessage::SlideColorR(_)
+            | CurveMessage::SlideColorG(_)
+            | CurveMessage::SlideColorB(_)
+            | CurveMessage::SlideColorA(_)
+            | CurveMessage::InputWidth(_)
+            | CurveMessage::InputFillR(_)
+            | CurveMessage::InputFillG(_)
+            | CurveMessage::InputFillB(_)
+            | CurveMessage::InputFillA(_)
+            | CurveMessage::SlideFillR(_)
+            | CurveMessage::SlideFillG(_)
+            | CurveMessage::SlideFillB(_)
+            | CurveMessage::SlideFillA(_)
+            | CurveMessage::InputSegments(_)
+            | CurveMessage::InputOffset(_)
+            | CurveMessage::InputMiterLimit(_)
+            | CurveMessage::SlideOpacity(_)
+            | CurveMessage::SetColor(..)
+            | CurveMessage::InputHex(..)
+            | CurveMessage::Transform(_) => (None, None),
+            _ => return None,
+        };
+        let handle = match message {
+            CurveMessage::SetColor(target, _) | CurveMessage::InputHex(target, _) => {
+                Some(format!("{:?}", target))
+            }
+            _ => handle,
+        };
+        Some(MergeKey {
+            target,
+            message: Some(discriminant(message)),
+            shape,
+            handle,
+            layer: None,
+        })
+    }
+
+    pub fn style_name() -> Self {
+        MergeKey {
+            target: None,
+            message: None,
+            shape: None,
+            handle: None,
+            layer: None,
+        }
+    }
+
+    pub fn layer(message: &LayerMessage) -> Self {
+        MergeKey {
+            layer: Some(discriminant(message)),
+            ..MergeKey::style_name()
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct History {
+    undo: VecDeque<EditCommand>,
+    redo: Vec<EditCommand>,
+    //上一次可以合并的修改，松开鼠标、撤销和重做之后清空
+    last: Option<MergeKey>,
+    depth: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History {
+            undo: VecDeque::new(),
+            redo: vec![],
+            last: None,
+            depth: History::DEFAULT_DEPTH,
+        }
+    }
+}
+
+impl History {
+    pub const DEFAULT_DEPTH: usize = 100;
+
+    //和上一次的key相同时合并到上一次的修改里
+    pub fn push(&mut self, command: EditCommand, key: Option<MergeKey>) {
+        self.redo.clear();
+
+        let merging = key.is_some() && key == self.last;
+        match self.undo.back_mut() {
+            Some(last) if merging && last.can_merge(&command) => last.merge(command),
+            _ => {
+                self.undo.push_back(command);
+                if self.undo.len() > self.depth {
+                    self.undo.pop_front();
+                }
+            }
+        }
+
+        self.last = key;
+    }
+
+    //一次拖动结束，之后的修改不再合并到前面
+    pub fn close_merge(&mut self) {
+        self.last = None;
+    }
+
+    pub fn undo(&mut self) -> Option<EditCommand> {
+        let command = self.undo.pop_back()?;
+        self.redo.push(command.clone());
+        self.last = None;
+        Some(command)
+    }
+
+    pub fn redo(&mut self) -> Option<EditCommand> {
+        let command = self.redo.pop()?;
+        self.undo.push_back(command.clone());
+        self.last = None;
+        Some(command)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    //调小深度时丢掉最早的记录
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth.max(1);
+        while self.undo.len() > self.depth {
+            self.undo.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(index: usize) -> EditCommand {
+        EditCommand::Insert {
+            index,
+            curve: Curve::default(),
+        }
+    }
+
+    fn modify(index: usize, before: f32, after: f32) -> EditCommand {
+        let curve = |opacity| {
+            Box::new(Curve {
+                opacity,
+                ..Curve::default()
+            })
+        };
+        EditCommand::Modify {
+            index,
+            before: curve(before),
+            after: curve(after),
+        }
+    }
+
+    fn index(command: Option<EditCommand>) -> Option<usize> {
+        match command? {
+            EditCommand::Insert { index, .. }
+            | EditCommand::Remove { index, .. }
+            | EditCommand::Modify { index, .. }
+            | EditCommand::Style { index, .. } => Some(index),
+            _ => None,
+        }
+    }
+
+    fn opacity_key(index: usize) -> Option<MergeKey> {
+        MergeKey::new(Some(index), &CurveMessage::SlideOpacity(0.0))
+    }
+
+    #[test]
+    fn undo_and_redo_in_order() {
+        let mut history = History::default();
+        for i in 0..3 {
+            history.push(insert(i), None);
+        }
+        assert_eq!(index(history.undo()), Some(2));
+        assert_eq!(index(history.undo()), Some(1));
+        assert_eq!(index(history.redo()), Some(1));
+        assert_eq!(index(history.undo()), Some(1));
+        assert_eq!(index(history.undo()), Some(0));
+        assert!(!history.can_undo());
+        assert!(history.can_redo());
+
+        //新的修改清空重做
+        history.push(insert(5), None);
+        assert!(!history.can_redo());
+        assert_eq!(index(history.undo()), Some(5));
+    }
+
+    #[test]
+    fn same_key_merges_until_closed() {
+        let mut history = History::default();
+        history.push(modify(0, 1.0, 0.8), opacity_key(0));
+        history.push(modify(0, 0.8, 0.5), opacity_key(0));
+        match history.undo() {
+            Some(EditCommand::Modify { before, after, .. }) => {
+                assert_eq!(before.opacity, 1.0);
+                assert_eq!(after.opacity, 0.5);
+            }
+            other => panic!("{:?}", other),
+        }
+        assert!(!history.can_undo());
+
+        history.redo();
+        history.push(modify(0, 0.5, 0.4), opacity_key(0));
+        history.close_merge();
+        history.push(modify(0, 0.4, 0.3), opacity_key(0));
+        history.undo();
+        history.undo();
+        history.undo();
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn toggles_are_recorded_separately() {
+        let mut history = History::default();
+        for fill in [true, false] {
+            let key = MergeKey::new(Some(0), &CurveMessage::FillToggled(fill));
+            assert_eq!(key, None);
+            history.push(modify(0, 1.0, 1.0), key);
+        }
+        assert!(history.undo().is_some());
+        assert!(history.undo().is_some());
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn different_keys_do_not_merge() {
+        let mut history = History::default();
+        history.push(modify(0, 1.0, 0.8), opacity_key(0));
+        history.push(modify(1, 1.0, 0.8), opacity_key(1));
+        history.push(modify(1, 0.8, 0.6), None);
+        history.push(modify(1, 0.6, 0.4), None);
+        assert_eq!(index(history.undo()), Some(1));
+        assert_eq!(index(history.undo()), Some(1));
+        assert_eq!(index(history.undo()), Some(1));
+        assert_eq!(index(history.undo()), Some(0));
+    }
+
+    #[test]
+    fn batches_merge_elementwise() {
+        let mut history = History::default();
+        let key = MergeKey::new(None, &CurveMessage::SlideOpacity(0.0));
+        history.push(
+            EditCommand::Batch(vec![modify(0, 1.0, 0.9), modify(1, 0.5, 0.4)]),
+            key.clone(),
+        );
+        history.push(
+            EditCommand::Batch(vec![modify(0, 0.9, 0.7), modify(1, 0.4, 0.2)]),
+            key,
+        );
+        match history.undo() {
+            Some(EditCommand::Batch(commands)) => {
+                let opacities = commands
+                    .iter()
+                    .map(|command| match command {
+                        EditCommand::Modify { before, after, .. } => {
+                            (before.opacity, after.opacity)
+                        }
+                        _ => panic!("{:?}", command),
+                    })
+                    .collect::<Vec<_>>();
+                assert_eq!(opacities, vec![(1.0, 0.7), (0.5, 0.2)]);
+            }
+            other => panic!("{:?}", other),
+        }
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn mismatched_batches_are_recorded_separately() {
+        let mut history = History::default();
+        let key = MergeKey::new(None, &CurveMessage::SlideOpacity(0.0));
+        history.push(EditCommand::Batch(vec![modify(0, 1.0, 0.9)]), key.clone());
+        //长度不同
+        history.push(
+            EditCommand::Batch(vec![modify(0, 0.9, 0.8), modify(1, 1.0, 0.8)]),
+            key.clone(),
+        );
+        //下标不同
+        history.push(
+            EditCommand::Batch(vec![modify(0, 0.8, 0.7), modify(2, 1.0, 0.7)]),
+            key,
+        );
+        let mut count = 0;
+        while history.undo().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn depth_drops_the_oldest() {
+        let mut history = History::default();
+        history.set_depth(3);
+        for i in 0..5 {
+            history.push(insert(i), None);
+        }
+        history.set_depth(2);
+        assert_eq!(history.depth(), 2);
+        assert_eq!(index(history.undo()), Some(4));
+        assert_eq!(index(history.undo()), Some(3));
+        assert!(!history.can_undo());
+
+        history.set_depth(0);
+        assert_eq!(history.depth(), 1);
+    }
+}
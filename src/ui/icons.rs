@@ -1,5 +1,7 @@
 use iced::{Font, Length, Text};
 
+use super::shape::FIRA_CODE;
+
 const ICONS: Font = Font::External {
     name: "Icons",
     bytes: include_bytes!("fonts/icons.ttf"),
@@ -17,6 +19,14 @@ fn icon(unicode: char) -> Text {
         .size(20)
 }
 
+//图标字体里没有几何图形，形状工具的图标用Fira Code里的字符
+fn shape(unicode: char) -> Text {
+    Text::new(unicode.to_string())
+        .font(FIRA_CODE)
+        .width(Length::Units(20))
+        .size(20)
+}
+
 pub fn delete() -> Text {
     icon('\u{F1F8}')
 }
@@ -33,16 +43,68 @@ pub fn load() -> Text {
     icon('\u{f115}')
 }
 
+pub fn line() -> Text {
+    shape('\u{2571}')
+}
+
 pub fn rectangle() -> Text {
-    icon('\u{25ad}')
+    shape('\u{25ad}')
 }
 
 pub fn triangle() -> Text {
-    icon('\u{25b3}')
+    shape('\u{2206}')
+}
+
+pub fn circle() -> Text {
+    shape('\u{25cb}')
+}
+
+pub fn ellipse() -> Text {
+    shape('\u{25ef}')
 }
 
 pub fn quadratic_bezier() -> Text {
-    icon('\u{25af}')
+    shape('\u{256d}')
+}
+
+pub fn cubic_bezier() -> Text {
+    shape('\u{222b}')
+}
+
+pub fn arc() -> Text {
+    shape('\u{25e0}')
+}
+
+pub fn path() -> Text {
+    shape('\u{221a}')
+}
+
+pub fn polyline() -> Text {
+    shape('\u{2227}')
+}
+
+pub fn polygon() -> Text {
+    shape('\u{2302}')
+}
+
+pub fn regular_polygon() -> Text {
+    shape('\u{25c7}')
+}
+
+pub fn star() -> Text {
+    shape('\u{263c}')
+}
+
+pub fn pencil() -> Text {
+    shape('\u{2248}')
+}
+
+pub fn text() -> Text {
+    shape('T')
+}
+
+pub fn curved_arrow() -> Text {
+    shape('\u{21a9}')
 }
 
 pub fn duplicate() -> Text {
@@ -69,14 +131,10 @@ pub fn duplicate() -> Text {
 //     icon('\u{f128}')
 // }
 
-// pub fn up_arrow() -> Text {
-//     icon('\u{e816}')
-// }
+pub fn up_arrow() -> Text {
+    icon('\u{e816}')
+}
 
 // pub fn down_arrow() -> Text {
 //     icon('\u{e813}')
 // }
-
-// pub fn cubic_bezier() -> Text {
-//     icon('\u{25af}')
-// }
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use svg::node::element::Group as SvgGroup;
+
+use super::utils::escape_xml;
+
+#[derive(Debug, Clone)]
+pub enum LayerMessage {
+    Added,
+    Removed,
+    //新的曲线画在当前图层上
+    Activated(usize),
+    Renamed(String),
+    VisibilityToggled(usize, bool),
+    LockToggled(usize, bool),
+    Raised(usize),
+    Lowered(usize),
+    SlideOpacity(f32),
+    //选中的曲线移到当前图层
+    SelectionMoved,
+}
+
+//图层按顺序叠放，后面的盖住前面的
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Layer {
+    pub name: String,
+    pub visible: bool,
+    //锁定之后不能在画布上选中
+    pub locked: bool,
+    pub opacity: f32,
+}
+
+impl Default for Layer {
+    fn default() -> Self {
+        Layer::new("Layer 1".to_string())
+    }
+}
+
+impl Layer {
+    pub const INKSCAPE: &'static str = "http://www.inkscape.org/namespaces/inkscape";
+    pub const SODIPODI: &'static str = "http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd";
+
+    pub fn new(name: String) -> Self {
+        Layer {
+            name,
+            visible: true,
+            locked: false,
+            opacity: 1.0,
+        }
+    }
+
+    //能不能在画布上看到和选中
+    pub fn is_selectable(&self) -> bool {
+        self.visible && !self.locked
+    }
+
+    //导出成inkscape能识别的图层
+    pub fn export_as_svg(&self, id: usize) -> SvgGroup {
+        let mut group = SvgGroup::new()
+            .set("id", format!("layer-{}", id))
+            .set("inkscape:label", escape_xml(&self.name))
+            .set("inkscape:groupmode", "layer");
+        if !self.visible {
+            group = group.set("style", "display:none");
+        }
+        if self.locked {
+            group = group.set("sodipodi:insensitive", "true");
+        }
+        if self.opacity < 1.0 {
+            group = group.set("opacity", self.opacity);
+        }
+        group
+    }
+}
@@ -0,0 +1,128 @@
+use iced::Color;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use super::curve::{Curve, EqLineCap, EqLineJoin};
+use crate::utils::{get_format_rgb, SerdeColor};
+
+//文档里共用的样式，曲线通过名字引用
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedStyle {
+    pub name: String,
+    #[serde_as(as = "SerdeColor")]
+    pub color: Color,
+    pub width: f32,
+    pub line_cap: EqLineCap,
+    pub line_join: EqLineJoin,
+    pub segments: Vec<f32>,
+    pub offset: f32,
+    #[serde_as(as = "Option<SerdeColor>")]
+    pub fill: Option<Color>,
+}
+
+impl NamedStyle {
+    pub fn from_curve(name: String, curve: &Curve) -> Self {
+        NamedStyle {
+            name,
+            color: curve.color,
+            width: curve.width,
+            line_cap: curve.line_cap,
+            line_join: curve.line_join,
+            segments: curve.segments.clone(),
+            offset: curve.offset,
+            fill: curve.fill,
+        }
+    }
+
+    //样式里只有纯色，应用之后渐变会被去掉
+    pub fn apply(&self, curve: &mut Curve) {
+        curve.color = self.color;
+        curve.width = self.width;
+        curve.line_cap = self.line_cap;
+        curve.line_join = self.line_join;
+        curve.segments = self.segments.clone();
+        curve.offset = self.offset;
+        curve.segments_input = None;
+        curve.offset_input = None;
+        curve.fill = self.fill;
+        curve.stroke_gradient = None;
+        curve.fill_gradient = None;
+        curve.style = Some(self.name.clone());
+    }
+
+    //编辑样式时借用曲线的编辑界面
+    pub fn template(&self) -> Curve {
+        let mut curve = Curve::default();
+        self.apply(&mut curve);
+        curve.style = None;
+        curve
+    }
+
+    pub fn export_as_css(&self) -> String {
+        let curve = self.template();
+        let mut rules = vec![
+            format!("stroke: {}", get_format_rgb(self.color)),
+            format!("stroke-opacity: {}", self.color.a),
+            format!("stroke-width: {}", self.width),
+            format!("stroke-linecap: {}", self.line_cap),
+            format!("stroke-linejoin: {}", self.line_join),
+        ];
+        if let Some((array, offset)) = curve.export_dash() {
+            rules.push(format!("stroke-dasharray: {}", array));
+            rules.push(format!("stroke-dashoffset: {}", offset));
+        }
+        match self.fill {
+            Some(fill) => {
+                rules.push(format!("fill: {}", get_format_rgb(fill)));
+                rules.push(format!("fill-opacity: {}", fill.a));
+            }
+            None => rules.push("fill: none".to_string()),
+        }
+
+        format!(".{} {{ {}; }}", class_name(&self.name), rules.join("; "))
+    }
+}
+
+//样式名里可能有空格或者中文之类的字符，不能直接当作css类名
+//其余的字符换成_加上十六进制的码位再加上_，不同的名字得到的类名也不同
+pub fn class_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c.to_string()
+            } else {
+                format!("_{:x}_", c as u32)
+            }
+        })
+        .collect::<String>();
+    format!("style-{}", name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::class_name;
+
+    #[test]
+    fn class_names_do_not_collide() {
+        let names = ["主色", "辅色", "a b", "a-b", "a_b", "a_20_b", "a.b"];
+        let classes = names.iter().map(|n| class_name(n)).collect::<Vec<_>>();
+        for (i, a) in classes.iter().enumerate() {
+            for b in &classes[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn class_names_are_valid_identifiers() {
+        for name in ["主色", "a b", "1st", "x.y"] {
+            let class = class_name(name);
+            assert!(class.starts_with("style-"));
+            assert!(class
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        }
+    }
+}
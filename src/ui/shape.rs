@@ -1,10 +1,24 @@
-use std::{collections::HashMap, fmt::Debug};
+use std::{
+    collections::HashMap,
+    f32::consts::PI,
+    fmt::{Debug, Display},
+};
 
-use iced::{canvas::Path, Point, Size};
+use iced::{
+    alignment::Horizontal,
+    canvas::{
+        path::{arc::Elliptical, Arc as CanvasArc, Builder},
+        Path, Text as CanvasText,
+    },
+    Font, Point, Size, Vector,
+};
 
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
-use svg::node::element::path::Data;
+use svg::node::element::{
+    path::Data, Circle as SvgCircle, Element, Marker as SvgMarker, Path as SvgPath, Text as SvgText,
+};
+use svg::node::Text as TextNode;
 
 use enum_dispatch::enum_dispatch;
 
@@ -12,11 +26,29 @@ use crate::ui::utils::get_size;
 use crate::utils::SerdePoint;
 use crate::utils::SerdeSize;
 
-use super::utils::get_radius;
+use super::transform::Transform;
+use super::utils::{escape_xml, get_radius, midpoint, simplify};
+
+pub(crate) const FIRA_CODE: Font = Font::External {
+    name: "Fira Code",
+    bytes: include_bytes!("fonts/firacode_m.ttf"),
+};
 
 #[derive(Debug, Clone)]
 pub enum ShapeMessage {
     Labor(Point),
+    LaborDrag(Point, Point),
+    Sample(Point),
+    Finish,
+    Tolerance(f32),
+    Attribute(String, String),
+    ArcMode(ArcMode),
+    TextContent(String),
+    TextSize(String),
+    TextFont(TextFont),
+    TextAlign(TextAlign),
+    StartMarker(Marker),
+    EndMarker(Marker),
     MovePoint(String, Point),
     Move(String, Point),
     Centered(Point),
@@ -24,13 +56,23 @@ pub enum ShapeMessage {
 }
 
 #[enum_dispatch]
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ShapeEnum {
     Line,
     Rectangle,
     Circle,
+    Ellipse,
+    Arc,
     Triangle,
     QuadraticBezier,
+    CubicBezier,
+    PathShape,
+    Polyline,
+    Polygon,
+    Star,
+    Freehand,
+    Text,
+    Arrow,
 }
 
 impl Default for ShapeEnum {
@@ -39,6 +81,32 @@ impl Default for ShapeEnum {
     }
 }
 
+impl Display for ShapeEnum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ShapeEnum::Line(_) => "Line",
+                ShapeEnum::Rectangle(_) => "Rectangle",
+                ShapeEnum::Circle(_) => "Circle",
+                ShapeEnum::Ellipse(_) => "Ellipse",
+                ShapeEnum::Arc(_) => "Arc",
+                ShapeEnum::Triangle(_) => "Triangle",
+                ShapeEnum::QuadraticBezier(_) => "Quadratic Bezier",
+                ShapeEnum::CubicBezier(_) => "Cubic Bezier",
+                ShapeEnum::PathShape(_) => "Path",
+                ShapeEnum::Polyline(_) => "Polyline",
+                ShapeEnum::Polygon(_) => "Polygon",
+                ShapeEnum::Star(_) => "Star",
+                ShapeEnum::Freehand(_) => "Freehand",
+                ShapeEnum::Text(_) => "Text",
+                ShapeEnum::Arrow(_) => "Arrow",
+            }
+        )
+    }
+}
+
 #[enum_dispatch(ShapeEnum)]
 pub trait Shape: Send + Debug {
     //utils
@@ -46,13 +114,25 @@ pub trait Shape: Send + Debug {
     fn is_complete(&self) -> bool;
     fn points(&self) -> HashMap<String, Point>;
     fn attributes(&self) -> HashMap<String, f32>;
+    //能通过ShapeMessage::Attribute修改的属性，其余的属性只能查看
+    fn is_editable(&self, _attribute: &str) -> bool {
+        false
+    }
 
     //manipulation
     fn update(&mut self, message: ShapeMessage);
+    //能把变换直接算到点上的图形返回true，其余的图形由曲线记录变换矩阵
+    fn transform(&mut self, _transform: &Transform) -> bool {
+        false
+    }
 
     //drawing
     fn preview(&self, cursor_position: Point) -> Option<Path>;
     fn draw(&self, selected: bool) -> (Option<Path>, Option<Path>);
+    //需要在画布上写字的图形才会用到
+    fn text(&self) -> Option<CanvasText> {
+        None
+    }
     fn export_as_svg(&self) -> Option<Data>;
 }
 
@@ -89,11 +169,14 @@ impl Shape for Line {
         let mut attrs = HashMap::new();
 
         if let (Some(from), Some(to)) = (self.from, self.to) {
-            attrs.insert(String::from("Length"), from.distance(to));
+            attrs.insert(String::from("length"), from.distance(to));
         }
 
         attrs
     }
+    fn is_editable(&self, attribute: &str) -> bool {
+        attribute == "length"
+    }
 
     fn update(&mut self, message: ShapeMessage) {
         match message {
@@ -104,6 +187,17 @@ impl Shape for Line {
                     self.to = Some(point);
                 }
             }
+            //起点不动，终点沿着原来的方向伸缩
+            ShapeMessage::Attribute(name, value) if name == "length" => {
+                if let (Ok(length), Some(from), Some(to)) =
+                    (value.parse::<f32>(), self.from, &mut self.to)
+                {
+                    let length_before = from.distance(*to);
+                    if length > 0.0 && length_before > 0.0 {
+                        *to = from + (*to - from) * (length / length_before);
+                    }
+                }
+            }
             ShapeMessage::MovePoint(index, point) => match index.as_str() {
                 "from" => {
                     if let Some(from) = &mut self.from {
@@ -146,12 +240,11 @@ impl Shape for Line {
                     }
                 }
             }
-            ShapeMessage::Reset => {
-                if !self.is_empty() {
-                    self.from = None;
-                    self.to = None;
-                }
+            ShapeMessage::Reset if !self.is_empty() => {
+                self.from = None;
+                self.to = None;
             }
+            _ => {}
         }
     }
 
@@ -184,6 +277,10 @@ impl Shape for Line {
             (None, None)
         }
     }
+    fn transform(&mut self, transform: &Transform) -> bool {
+        transform_points(self.from.iter_mut().chain(self.to.iter_mut()), transform);
+        true
+    }
     fn export_as_svg(&self) -> Option<Data> {
         if let (Some(Point { x: x1, y: y1 }), Some(Point { x: x2, y: y2 })) = (self.from, self.to) {
             {
@@ -202,6 +299,89 @@ pub struct Rectangle {
     pub(crate) top_left: Option<Point>,
     #[serde_as(as = "Option<SerdeSize>")]
     pub(crate) size: Option<Size>,
+    //圆角半径，顺序是左上、右上、右下、左下
+    #[serde(default)]
+    pub(crate) corner_radii: [f32; 4],
+}
+
+impl Rectangle {
+    const RADIUS_HANDLE_OFFSET: f32 = 15.0;
+    //用三次贝塞尔曲线近似四分之一圆弧
+    const KAPPA: f32 = 0.552_284_8;
+    const CORNERS: [&'static str; 4] = ["top_left", "top_right", "bottom_right", "bottom_left"];
+
+    //size可能是负数，先换算成左上角和右下角
+    fn bounds(&self) -> Option<(Point, Point)> {
+        if let (Some(top_left), Some(size)) = (self.top_left, self.size) {
+            let (x1, x2) = (top_left.x, top_left.x + size.width);
+            let (y1, y2) = (top_left.y, top_left.y + size.height);
+            Some((
+                Point::new(x1.min(x2), y1.min(y2)),
+                Point::new(x1.max(x2), y1.max(y2)),
+            ))
+        } else {
+            None
+        }
+    }
+
+    fn max_radius(&self) -> f32 {
+        self.size
+            .map(|size| size.width.abs().min(size.height.abs()) / 2.0)
+            .unwrap_or(0.0)
+    }
+
+    //从右上角开始顺时针排列的角点，入边方向，出边方向，以及限制过的半径
+    fn corners(&self) -> Option<[(Point, Vector, Vector, f32); 4]> {
+        let (min, max) = self.bounds()?;
+        let radius = |r: f32| r.clamp(0.0, self.max_radius());
+        let [top_left, top_right, bottom_right, bottom_left] = self.corner_radii;
+        Some([
+            (
+                Point::new(max.x, min.y),
+                Vector::new(1.0, 0.0),
+                Vector::new(0.0, 1.0),
+                radius(top_right),
+            ),
+            (
+                max,
+                Vector::new(0.0, 1.0),
+                Vector::new(-1.0, 0.0),
+                radius(bottom_right),
+            ),
+            (
+                Point::new(min.x, max.y),
+                Vector::new(-1.0, 0.0),
+                Vector::new(0.0, -1.0),
+                radius(bottom_left),
+            ),
+            (
+                min,
+                Vector::new(0.0, -1.0),
+                Vector::new(1.0, 0.0),
+                radius(top_left),
+            ),
+        ])
+    }
+
+    fn build(&self, builder: &mut Builder) {
+        if let Some(corners) = self.corners() {
+            let (corner, _, out, r) = corners[3];
+            builder.move_to(corner + out * r);
+            for (corner, into, out, r) in corners {
+                let start = corner + into * -r;
+                builder.line_to(start);
+                if r > 0.0 {
+                    let end = corner + out * r;
+                    builder.bezier_curve_to(
+                        start + into * (r * Rectangle::KAPPA),
+                        end + out * (-r * Rectangle::KAPPA),
+                        end,
+                    );
+                }
+            }
+            builder.close();
+        }
+    }
 }
 
 impl Shape for Rectangle {
@@ -242,6 +422,18 @@ impl Shape for Rectangle {
             }
         }
 
+        if let Some((min, _)) = self.bounds() {
+            points.insert(
+                String::from("radius"),
+                Point {
+                    x: min.x
+                        + self.corner_radii[0].clamp(0.0, self.max_radius())
+                        + Rectangle::RADIUS_HANDLE_OFFSET,
+                    y: min.y,
+                },
+            );
+        }
+
         points
     }
     fn attributes(&self) -> HashMap<String, f32> {
@@ -250,10 +442,20 @@ impl Shape for Rectangle {
         if let Some(size) = self.size {
             attrs.insert(String::from("width"), size.width);
             attrs.insert(String::from("height"), size.height);
+            attrs.insert(String::from("radius"), self.corner_radii[0]);
+            for (corner, radius) in Rectangle::CORNERS.iter().zip(self.corner_radii) {
+                attrs.insert(format!("radius_{}", corner), radius);
+            }
         }
 
         attrs
     }
+    fn is_editable(&self, attribute: &str) -> bool {
+        matches!(attribute, "width" | "height" | "radius")
+            || Rectangle::CORNERS
+                .iter()
+                .any(|corner| attribute == format!("radius_{}", corner))
+    }
 
     fn update(&mut self, message: ShapeMessage) {
         match message {
@@ -294,8 +496,42 @@ impl Shape for Rectangle {
                         size.height = point.y - top_left.y;
                     }
                 }
+                //圆角柄在上边上，拖动时统一修改四个角
+                "radius" => {
+                    if let Some((min, _)) = self.bounds() {
+                        let radius = (point.x - min.x - Rectangle::RADIUS_HANDLE_OFFSET)
+                            .clamp(0.0, self.max_radius());
+                        self.corner_radii = [radius; 4];
+                    }
+                }
                 _ => {}
             },
+            ShapeMessage::Attribute(name, value) => {
+                if let Ok(value) = value.parse::<f32>() {
+                    let value = value.max(0.0);
+                    if let (Some(size), "width" | "height") = (&mut self.size, name.as_str()) {
+                        if value > 0.0 {
+                            if name == "width" {
+                                size.width = value;
+                            } else {
+                                size.height = value;
+                            }
+                        }
+                    } else if name == "radius" {
+                        self.corner_radii = [value; 4];
+                    } else if let Some(i) = Rectangle::CORNERS
+                        .iter()
+                        .position(|corner| name == format!("radius_{}", corner))
+                    {
+                        self.corner_radii[i] = value;
+                    }
+                }
+            }
+            ShapeMessage::Move(index, point) if index == "radius" => {
+                if let (Some(p), Some(top_left)) = (self.points().get(&index), &mut self.top_left) {
+                    *top_left = *top_left + (point - *p);
+                }
+            }
             ShapeMessage::Move(index, point) => {
                 if let (Some(top_left), Some(size)) = (&mut self.top_left, &mut self.size) {
                     let (x, y) = match index.as_str() {
@@ -323,12 +559,12 @@ impl Shape for Rectangle {
                     }
                 }
             }
-            ShapeMessage::Reset => {
-                if !self.is_empty() {
-                    self.top_left = None;
-                    self.size = None;
-                }
+            ShapeMessage::Reset if !self.is_empty() => {
+                self.top_left = None;
+                self.size = None;
+                self.corner_radii = [0.0; 4];
             }
+            _ => {}
         }
     }
 
@@ -338,7 +574,7 @@ impl Shape for Rectangle {
         })
     }
     fn draw(&self, selected: bool) -> (Option<Path>, Option<Path>) {
-        if let (Some(top_left), Some(size)) = (self.top_left, self.size) {
+        if self.is_complete() {
             let selected = if selected {
                 Some(Path::new(|b| {
                     for (_, point) in self.points() {
@@ -348,31 +584,30 @@ impl Shape for Rectangle {
             } else {
                 None
             };
-            (
-                Some(Path::new(|builder| {
-                    builder.rectangle(top_left, size);
-                })),
-                selected,
-            )
+            (Some(Path::new(|builder| self.build(builder))), selected)
         } else {
             (None, None)
         }
     }
     fn export_as_svg(&self) -> Option<Data> {
-        if let (Some(top_left), Some(size)) = (self.top_left, self.size) {
-            let data = Data::new();
-            let (x1, y1) = (top_left.x, top_left.y);
-            let (x2, y2) = (x1 + size.width, y1 + size.height);
-            Some(
-                data.move_to((x1, y1))
-                    .line_to((x2, y1))
-                    .line_to((x2, y2))
-                    .line_to((x1, y2))
-                    .line_to((x1, y1)),
-            )
-        } else {
-            None
-        }
+        let corners = self.corners()?;
+        let (corner, _, out, r) = corners[3];
+        let start = corner + out * r;
+        //圆角用顺时针的圆弧
+        let data = corners.into_iter().fold(
+            Data::new().move_to((start.x, start.y)),
+            |acc, (corner, into, out, r)| {
+                let start = corner + into * -r;
+                let end = corner + out * r;
+                let acc = acc.line_to((start.x, start.y));
+                if r > 0.0 {
+                    acc.elliptical_arc_to(vec![r, r, 0.0, 0.0, 1.0, end.x, end.y])
+                } else {
+                    acc
+                }
+            },
+        );
+        Some(data.close())
     }
 }
 
@@ -478,13 +713,12 @@ impl Shape for Triangle {
                     }
                 }
             }
-            ShapeMessage::Reset => {
-                if !self.is_empty() {
-                    self.a = None;
-                    self.b = None;
-                    self.c = None;
-                }
+            ShapeMessage::Reset if !self.is_empty() => {
+                self.a = None;
+                self.b = None;
+                self.c = None;
             }
+            _ => {}
         }
     }
 
@@ -512,7 +746,7 @@ impl Shape for Triangle {
             let points = self.points();
             let selected = if selected {
                 Some(Path::new(|builder| {
-                    for (_, point) in points.iter() {
+                    for point in points.values() {
                         builder.circle(*point, 5.0);
                     }
                 }))
@@ -536,6 +770,15 @@ impl Shape for Triangle {
             (None, None)
         }
     }
+    fn transform(&mut self, transform: &Transform) -> bool {
+        transform_points(
+            [&mut self.a, &mut self.b, &mut self.c]
+                .into_iter()
+                .flatten(),
+            transform,
+        );
+        true
+    }
     fn export_as_svg(&self) -> Option<Data> {
         let points = self.points();
         if self.is_complete() {
@@ -663,13 +906,12 @@ impl Shape for QuadraticBezier {
                     }
                 }
             }
-            ShapeMessage::Reset => {
-                if !self.is_empty() {
-                    self.a = None;
-                    self.b = None;
-                    self.control = None;
-                }
+            ShapeMessage::Reset if !self.is_empty() => {
+                self.a = None;
+                self.b = None;
+                self.control = None;
             }
+            _ => {}
         }
     }
 
@@ -718,6 +960,15 @@ impl Shape for QuadraticBezier {
             (None, None)
         }
     }
+    fn transform(&mut self, transform: &Transform) -> bool {
+        transform_points(
+            [&mut self.a, &mut self.b, &mut self.control]
+                .into_iter()
+                .flatten(),
+            transform,
+        );
+        true
+    }
     fn export_as_svg(&self) -> Option<Data> {
         if let (Some(a), Some(b), Some(control)) = (self.a, self.b, self.control) {
             Some(
@@ -731,6 +982,222 @@ impl Shape for QuadraticBezier {
     }
 }
 
+#[serde_as]
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct CubicBezier {
+    #[serde_as(as = "Option<SerdePoint>")]
+    pub(crate) a: Option<Point>,
+    #[serde_as(as = "Option<SerdePoint>")]
+    pub(crate) b: Option<Point>,
+    #[serde_as(as = "Option<SerdePoint>")]
+    pub(crate) control_a: Option<Point>,
+    #[serde_as(as = "Option<SerdePoint>")]
+    pub(crate) control_b: Option<Point>,
+}
+
+impl Shape for CubicBezier {
+    fn is_complete(&self) -> bool {
+        self.a.is_some() && self.b.is_some() && self.control_a.is_some() && self.control_b.is_some()
+    }
+    fn is_empty(&self) -> bool {
+        self.a.is_none() && self.b.is_none() && self.control_a.is_none() && self.control_b.is_none()
+    }
+    fn points(&self) -> HashMap<String, Point> {
+        let mut points = HashMap::new();
+
+        if let Some(a) = self.a {
+            points.insert(String::from("a"), a);
+        }
+
+        if let Some(b) = self.b {
+            points.insert(String::from("b"), b);
+        }
+
+        if let Some(control_a) = self.control_a {
+            points.insert(String::from("control_a"), control_a);
+        }
+
+        if let Some(control_b) = self.control_b {
+            points.insert(String::from("control_b"), control_b);
+        }
+
+        points
+    }
+    fn attributes(&self) -> HashMap<String, f32> {
+        HashMap::new()
+    }
+
+    fn update(&mut self, message: ShapeMessage) {
+        match message {
+            ShapeMessage::Labor(point) => {
+                if self.a.is_none() {
+                    self.a = Some(point);
+                } else if self.b.is_none() {
+                    self.b = Some(point);
+                } else if self.control_a.is_none() {
+                    self.control_a = Some(point);
+                } else if self.control_b.is_none() {
+                    self.control_b = Some(point);
+                }
+            }
+            ShapeMessage::MovePoint(index, point) => match index.as_str() {
+                "a" => {
+                    if let Some(a) = &mut self.a {
+                        *a = point;
+                    }
+                }
+                "b" => {
+                    if let Some(b) = &mut self.b {
+                        *b = point;
+                    }
+                }
+                "control_a" => {
+                    if let Some(control_a) = &mut self.control_a {
+                        *control_a = point;
+                    }
+                }
+                "control_b" => {
+                    if let Some(control_b) = &mut self.control_b {
+                        *control_b = point;
+                    }
+                }
+                _ => {}
+            },
+            ShapeMessage::Move(index, point) => {
+                if let (Some(a), Some(b), Some(control_a), Some(control_b)) = (
+                    &mut self.a,
+                    &mut self.b,
+                    &mut self.control_a,
+                    &mut self.control_b,
+                ) {
+                    let (x, y) = match index.as_str() {
+                        "a" => (point.x - a.x, point.y - a.y),
+                        "b" => (point.x - b.x, point.y - b.y),
+                        "control_a" => (point.x - control_a.x, point.y - control_a.y),
+                        "control_b" => (point.x - control_b.x, point.y - control_b.y),
+                        _ => (0.0, 0.0),
+                    };
+                    for p in [a, b, control_a, control_b] {
+                        p.x += x;
+                        p.y += y;
+                    }
+                }
+            }
+            ShapeMessage::Centered(p) => {
+                if let (Some(a), Some(b)) = (self.a, self.b) {
+                    let center_x = (a.x + b.x) / 2.0;
+                    let center_y = (a.y + b.y) / 2.0;
+
+                    let x = p.x - center_x;
+                    let y = p.y - center_y;
+
+                    if let (Some(a), Some(b), Some(control_a), Some(control_b)) = (
+                        &mut self.a,
+                        &mut self.b,
+                        &mut self.control_a,
+                        &mut self.control_b,
+                    ) {
+                        for p in [a, b, control_a, control_b] {
+                            p.x += x;
+                            p.y += y;
+                        }
+                    }
+                }
+            }
+            ShapeMessage::Reset if !self.is_empty() => {
+                self.a = None;
+                self.b = None;
+                self.control_a = None;
+                self.control_b = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn preview(&self, cursor_position: Point) -> Option<Path> {
+        if let Some(a) = self.a {
+            if let Some(b) = self.b {
+                //第一个控制点确定之前，两个控制点都跟随光标
+                let control_a = self.control_a.unwrap_or(cursor_position);
+                Some(Path::new(|p| {
+                    p.move_to(a);
+                    p.bezier_curve_to(control_a, cursor_position, b);
+                }))
+            } else {
+                Some(Path::new(|p| {
+                    p.move_to(a);
+                    p.line_to(cursor_position);
+                }))
+            }
+        } else {
+            None
+        }
+    }
+    fn draw(&self, selected: bool) -> (Option<Path>, Option<Path>) {
+        if let (Some(a), Some(b), Some(control_a), Some(control_b)) =
+            (self.a, self.b, self.control_a, self.control_b)
+        {
+            let to_fill = if selected {
+                Some(Path::new(|builder| {
+                    builder.circle(a, 5.0);
+                    builder.circle(b, 5.0);
+                    builder.circle(control_a, 5.0);
+                    builder.circle(control_b, 5.0);
+                }))
+            } else {
+                None
+            };
+            (
+                Some(Path::new(|builder| {
+                    builder.move_to(a);
+                    builder.bezier_curve_to(control_a, control_b, b);
+
+                    //两个控制柄分别连到各自的端点上
+                    if selected {
+                        builder.move_to(a);
+                        builder.line_to(control_a);
+                        builder.move_to(b);
+                        builder.line_to(control_b);
+                    }
+                })),
+                to_fill,
+            )
+        } else {
+            (None, None)
+        }
+    }
+    fn transform(&mut self, transform: &Transform) -> bool {
+        transform_points(
+            [
+                &mut self.a,
+                &mut self.b,
+                &mut self.control_a,
+                &mut self.control_b,
+            ]
+            .into_iter()
+            .flatten(),
+            transform,
+        );
+        true
+    }
+    fn export_as_svg(&self) -> Option<Data> {
+        if let (Some(a), Some(b), Some(control_a), Some(control_b)) =
+            (self.a, self.b, self.control_a, self.control_b)
+        {
+            Some(Data::new().move_to((a.x, a.y)).cubic_curve_to(vec![
+                control_a.x,
+                control_a.y,
+                control_b.x,
+                control_b.y,
+                b.x,
+                b.y,
+            ]))
+        } else {
+            None
+        }
+    }
+}
+
 #[serde_as]
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
 pub struct Circle {
@@ -794,9 +1261,19 @@ impl Shape for Circle {
 
         attrs
     }
+    fn is_editable(&self, attribute: &str) -> bool {
+        attribute == "radius"
+    }
 
     fn update(&mut self, message: ShapeMessage) {
         match message {
+            ShapeMessage::Attribute(name, value) if name == "radius" => {
+                if let (Ok(value), Some(radius)) = (value.parse::<f32>(), &mut self.radius) {
+                    if value > 0.0 {
+                        *radius = value;
+                    }
+                }
+            }
             ShapeMessage::Labor(point) => {
                 if self.center.is_none() {
                     self.center = Some(point);
@@ -841,12 +1318,11 @@ impl Shape for Circle {
                     *center = p;
                 }
             }
-            ShapeMessage::Reset => {
-                if !self.is_empty() {
-                    self.center = None;
-                    self.radius = None;
-                }
+            ShapeMessage::Reset if !self.is_empty() => {
+                self.center = None;
+                self.radius = None;
             }
+            _ => {}
         }
     }
 
@@ -884,6 +1360,17 @@ impl Shape for Circle {
             (None, None)
         }
     }
+    //等比缩放之后还是圆，其余的变换交给曲线的变换矩阵
+    fn transform(&mut self, transform: &Transform) -> bool {
+        let Some(scale) = transform.uniform_scale() else {
+            return false;
+        };
+        transform_points(self.center.iter_mut(), transform);
+        if let Some(radius) = &mut self.radius {
+            *radius *= scale;
+        }
+        true
+    }
     fn export_as_svg(&self) -> Option<Data> {
         if let (Some(center), Some(radius)) = (self.center, self.radius) {
             Some(
@@ -904,3 +1391,1902 @@ impl Shape for Circle {
         }
     }
 }
+
+#[serde_as]
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct Ellipse {
+    #[serde_as(as = "Option<SerdePoint>")]
+    pub(crate) center: Option<Point>,
+    pub(crate) rx: Option<f32>,
+    pub(crate) ry: Option<f32>,
+    //弧度，顺时针为正
+    pub(crate) rotation: f32,
+}
+
+impl Ellipse {
+    const ROTATION_HANDLE_OFFSET: f32 = 20.0;
+
+    //旋转之后x轴和y轴方向的单位向量
+    fn axes(&self) -> (Vector, Vector) {
+        let (sin, cos) = self.rotation.sin_cos();
+        (Vector::new(cos, sin), Vector::new(-sin, cos))
+    }
+
+    fn build(builder: &mut Builder, center: Point, rx: f32, ry: f32, rotation: f32) {
+        builder.ellipse(Elliptical {
+            center,
+            radii: Vector::new(rx, ry),
+            rotation,
+            start_angle: 0.0,
+            end_angle: 2.0 * PI,
+        });
+        builder.close();
+    }
+}
+
+impl Shape for Ellipse {
+    fn is_complete(&self) -> bool {
+        self.center.is_some() && self.rx.is_some() && self.ry.is_some()
+    }
+    fn is_empty(&self) -> bool {
+        self.center.is_none() && self.rx.is_none() && self.ry.is_none()
+    }
+    fn points(&self) -> HashMap<String, Point> {
+        let mut points = HashMap::new();
+
+        if let Some(center) = self.center {
+            points.insert(String::from("center"), center);
+            if let (Some(rx), Some(ry)) = (self.rx, self.ry) {
+                let (x_axis, y_axis) = self.axes();
+                points.insert(String::from("rx"), center + x_axis * rx);
+                points.insert(String::from("ry"), center + y_axis * -ry);
+                points.insert(
+                    String::from("rotation"),
+                    center + x_axis * (rx + Ellipse::ROTATION_HANDLE_OFFSET),
+                );
+            }
+        }
+
+        points
+    }
+    fn attributes(&self) -> HashMap<String, f32> {
+        let mut attrs = HashMap::new();
+
+        if let (Some(rx), Some(ry)) = (self.rx, self.ry) {
+            attrs.insert(String::from("rx"), rx);
+            attrs.insert(String::from("ry"), ry);
+            attrs.insert(String::from("rotation"), self.rotation.to_degrees());
+        }
+
+        attrs
+    }
+    fn is_editable(&self, attribute: &str) -> bool {
+        matches!(attribute, "rx" | "ry" | "rotation")
+    }
+
+    fn update(&mut self, message: ShapeMessage) {
+        match message {
+            ShapeMessage::Labor(point) => {
+                if self.center.is_none() {
+                    self.center = Some(point);
+                } else if let (Some(center), None, None) = (self.center, self.rx, self.ry) {
+                    self.rx = Some((point.x - center.x).abs());
+                    self.ry = Some((point.y - center.y).abs());
+                    self.rotation = 0.0;
+                }
+            }
+            ShapeMessage::Attribute(name, value) => {
+                if let (Ok(value), Some(rx), Some(ry)) =
+                    (value.parse::<f32>(), &mut self.rx, &mut self.ry)
+                {
+                    match name.as_str() {
+                        "rx" if value > 0.0 => *rx = value,
+                        "ry" if value > 0.0 => *ry = value,
+                        "rotation" => self.rotation = value.to_radians(),
+                        _ => {}
+                    }
+                }
+            }
+            ShapeMessage::MovePoint(index, point) => {
+                let (x_axis, y_axis) = self.axes();
+                if let (Some(center), Some(rx), Some(ry)) =
+                    (&mut self.center, &mut self.rx, &mut self.ry)
+                {
+                    let offset = point - *center;
+                    match index.as_str() {
+                        "center" => *center = point,
+                        //拖动轴上的点只改变对应的半径，投影到旋转后的轴上
+                        "rx" => *rx = (offset.x * x_axis.x + offset.y * x_axis.y).abs(),
+                        "ry" => *ry = (offset.x * y_axis.x + offset.y * y_axis.y).abs(),
+                        "rotation" => self.rotation = offset.y.atan2(offset.x),
+                        _ => {}
+                    }
+                }
+            }
+            ShapeMessage::Move(index, point) => {
+                if let Some(p) = self.points().get(&index) {
+                    if let Some(center) = &mut self.center {
+                        *center = *center + (point - *p);
+                    }
+                }
+            }
+            ShapeMessage::Centered(p) => {
+                if let Some(center) = &mut self.center {
+                    *center = p;
+                }
+            }
+            ShapeMessage::Reset if !self.is_empty() => {
+                self.center = None;
+                self.rx = None;
+                self.ry = None;
+                self.rotation = 0.0;
+            }
+            _ => {}
+        }
+    }
+
+    fn preview(&self, cursor_position: Point) -> Option<Path> {
+        self.center.map(|center| {
+            Path::new(|builder| match (self.rx, self.ry) {
+                (Some(rx), Some(ry)) => Ellipse::build(builder, center, rx, ry, self.rotation),
+                _ => Ellipse::build(
+                    builder,
+                    center,
+                    (cursor_position.x - center.x).abs(),
+                    (cursor_position.y - center.y).abs(),
+                    0.0,
+                ),
+            })
+        })
+    }
+    fn draw(&self, selected: bool) -> (Option<Path>, Option<Path>) {
+        if let (Some(center), Some(rx), Some(ry)) = (self.center, self.rx, self.ry) {
+            let points = self.points();
+            let to_fill = if selected {
+                Some(Path::new(|builder| {
+                    for point in points.values() {
+                        builder.circle(*point, 5.0);
+                    }
+                }))
+            } else {
+                None
+            };
+            (
+                Some(Path::new(|builder| {
+                    Ellipse::build(builder, center, rx, ry, self.rotation);
+
+                    //旋转柄和x轴上的点连成一条线
+                    if selected {
+                        if let (Some(from), Some(to)) = (points.get("rx"), points.get("rotation")) {
+                            builder.move_to(*from);
+                            builder.line_to(*to);
+                        }
+                    }
+                })),
+                to_fill,
+            )
+        } else {
+            (None, None)
+        }
+    }
+    //旋转和等比缩放改中心、半径和旋转角，其余的变换交给曲线的变换矩阵
+    fn transform(&mut self, transform: &Transform) -> bool {
+        let Some(scale) = transform.uniform_scale() else {
+            return false;
+        };
+        let (x_axis, _) = self.axes();
+        let x_axis = transform.apply_vector(x_axis);
+        transform_points(self.center.iter_mut(), transform);
+        for radius in [&mut self.rx, &mut self.ry].into_iter().flatten() {
+            *radius *= scale;
+        }
+        self.rotation = x_axis.y.atan2(x_axis.x);
+        true
+    }
+    fn export_as_svg(&self) -> Option<Data> {
+        if let (Some(center), Some(rx), Some(ry)) = (self.center, self.rx, self.ry) {
+            //分成两段半椭圆弧，起点和终点分别是长轴的两端
+            let (x_axis, _) = self.axes();
+            let start = center + x_axis * rx;
+            let end = center + x_axis * -rx;
+            let rotation = self.rotation.to_degrees();
+            Some(
+                Data::new()
+                    .move_to((start.x, start.y))
+                    .elliptical_arc_to(vec![rx, ry, rotation, 0.0, 1.0, end.x, end.y])
+                    .elliptical_arc_to(vec![rx, ry, rotation, 0.0, 1.0, start.x, start.y])
+                    .close(),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArcMode {
+    #[default]
+    Open,
+    Chord,
+    Pie,
+}
+
+impl ArcMode {
+    pub const ALL: [ArcMode; 3] = [ArcMode::Open, ArcMode::Chord, ArcMode::Pie];
+}
+
+impl Display for ArcMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ArcMode::Open => "open",
+                ArcMode::Chord => "chord",
+                ArcMode::Pie => "pie",
+            }
+        )
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct Arc {
+    #[serde_as(as = "Option<SerdePoint>")]
+    pub(crate) center: Option<Point>,
+    pub(crate) radius: Option<f32>,
+    //起始角和扫过的角度，弧度，顺时针为正，sweep在[0, 2π)之间
+    pub(crate) start: f32,
+    pub(crate) sweep: Option<f32>,
+    pub(crate) mode: ArcMode,
+}
+
+impl Arc {
+    fn at(center: Point, radius: f32, angle: f32) -> Point {
+        let (sin, cos) = angle.sin_cos();
+        center + Vector::new(cos, sin) * radius
+    }
+
+    fn angle(center: Point, point: Point) -> f32 {
+        let offset = point - center;
+        offset.y.atan2(offset.x)
+    }
+
+    fn build(
+        builder: &mut Builder,
+        center: Point,
+        radius: f32,
+        start: f32,
+        sweep: f32,
+        mode: ArcMode,
+    ) {
+        builder.arc(CanvasArc {
+            center,
+            radius,
+            start_angle: start,
+            end_angle: start + sweep,
+        });
+        match mode {
+            ArcMode::Open => {}
+            ArcMode::Chord => builder.close(),
+            ArcMode::Pie => {
+                builder.line_to(center);
+                builder.close();
+            }
+        }
+    }
+}
+
+impl Shape for Arc {
+    fn is_complete(&self) -> bool {
+        self.center.is_some() && self.radius.is_some() && self.sweep.is_some()
+    }
+    fn is_empty(&self) -> bool {
+        self.center.is_none() && self.radius.is_none() && self.sweep.is_none()
+    }
+    fn points(&self) -> HashMap<String, Point> {
+        let mut points = HashMap::new();
+
+        if let Some(center) = self.center {
+            points.insert(String::from("center"), center);
+            if let Some(radius) = self.radius {
+                points.insert(String::from("start"), Arc::at(center, radius, self.start));
+                if let Some(sweep) = self.sweep {
+                    points.insert(
+                        String::from("end"),
+                        Arc::at(center, radius, self.start + sweep),
+                    );
+                }
+            }
+        }
+
+        points
+    }
+    fn attributes(&self) -> HashMap<String, f32> {
+        let mut attrs = HashMap::new();
+
+        if let (Some(radius), Some(sweep)) = (self.radius, self.sweep) {
+            attrs.insert(String::from("radius"), radius);
+            attrs.insert(String::from("start"), self.start.to_degrees());
+            attrs.insert(String::from("sweep"), sweep.to_degrees());
+        }
+
+        attrs
+    }
+    fn is_editable(&self, attribute: &str) -> bool {
+        matches!(attribute, "radius" | "start" | "sweep")
+    }
+
+    fn update(&mut self, message: ShapeMessage) {
+        match message {
+            ShapeMessage::Labor(point) => match (self.center, self.radius, self.sweep) {
+                (None, _, _) => self.center = Some(point),
+                (Some(center), None, _) => {
+                    self.radius = Some(get_radius(center, point));
+                    self.start = Arc::angle(center, point);
+                }
+                (Some(center), Some(_), None) => {
+                    self.sweep =
+                        Some((Arc::angle(center, point) - self.start).rem_euclid(2.0 * PI));
+                }
+                _ => {}
+            },
+            ShapeMessage::ArcMode(mode) => self.mode = mode,
+            ShapeMessage::Attribute(name, value) => {
+                if let (Ok(value), Some(radius), Some(sweep)) =
+                    (value.parse::<f32>(), &mut self.radius, &mut self.sweep)
+                {
+                    match name.as_str() {
+                        "radius" if value > 0.0 => *radius = value,
+                        "start" => self.start = value.to_radians(),
+                        "sweep" => *sweep = value.to_radians().rem_euclid(2.0 * PI),
+                        _ => {}
+                    }
+                }
+            }
+            ShapeMessage::MovePoint(index, point) => {
+                if let (Some(center), Some(radius), Some(sweep)) =
+                    (&mut self.center, &mut self.radius, &mut self.sweep)
+                {
+                    match index.as_str() {
+                        "center" => *center = point,
+                        //拖动起点时终点不动
+                        "start" => {
+                            let end = self.start + *sweep;
+                            self.start = Arc::angle(*center, point);
+                            *sweep = (end - self.start).rem_euclid(2.0 * PI);
+                            *radius = get_radius(*center, point);
+                        }
+                        "end" => {
+                            *sweep = (Arc::angle(*center, point) - self.start).rem_euclid(2.0 * PI);
+                            *radius = get_radius(*center, point);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            ShapeMessage::Move(index, point) => {
+                if let Some(p) = self.points().get(&index) {
+                    if let Some(center) = &mut self.center {
+                        *center = *center + (point - *p);
+                    }
+                }
+            }
+            ShapeMessage::Centered(p) => {
+                if let Some(center) = &mut self.center {
+                    *center = p;
+                }
+            }
+            ShapeMessage::Reset if !self.is_empty() => {
+                self.center = None;
+                self.radius = None;
+                self.sweep = None;
+                self.start = 0.0;
+            }
+            _ => {}
+        }
+    }
+
+    fn preview(&self, cursor_position: Point) -> Option<Path> {
+        self.center.map(|center| {
+            Path::new(|builder| match self.radius {
+                Some(radius) => {
+                    let sweep =
+                        (Arc::angle(center, cursor_position) - self.start).rem_euclid(2.0 * PI);
+                    Arc::build(builder, center, radius, self.start, sweep, self.mode);
+                }
+                None => {
+                    builder.move_to(center);
+                    builder.line_to(cursor_position);
+                }
+            })
+        })
+    }
+    fn draw(&self, selected: bool) -> (Option<Path>, Option<Path>) {
+        if let (Some(center), Some(radius), Some(sweep)) = (self.center, self.radius, self.sweep) {
+            let to_fill = if selected {
+                Some(Path::new(|builder| {
+                    for point in self.points().values() {
+                        builder.circle(*point, 5.0);
+                    }
+                }))
+            } else {
+                None
+            };
+            (
+                Some(Path::new(|builder| {
+                    Arc::build(builder, center, radius, self.start, sweep, self.mode)
+                })),
+                to_fill,
+            )
+        } else {
+            (None, None)
+        }
+    }
+    fn export_as_svg(&self) -> Option<Data> {
+        if let (Some(center), Some(radius), Some(sweep)) = (self.center, self.radius, self.sweep) {
+            let start = Arc::at(center, radius, self.start);
+            let end = Arc::at(center, radius, self.start + sweep);
+            //超过半圆时用大弧，顺时针对应sweep-flag为1
+            let large_arc = if sweep > PI { 1.0 } else { 0.0 };
+            let data = Data::new()
+                .move_to((start.x, start.y))
+                .elliptical_arc_to(vec![radius, radius, 0.0, large_arc, 1.0, end.x, end.y]);
+            Some(match self.mode {
+                ArcMode::Open => data,
+                ArcMode::Chord => data.close(),
+                ArcMode::Pie => data.line_to((center.x, center.y)).close(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PathNode {
+    #[serde_as(as = "SerdePoint")]
+    pub(crate) point: Point,
+    //出方向的控制柄，入方向的控制柄由它关于节点对称得到；为None时是尖角节点
+    #[serde_as(as = "Option<SerdePoint>")]
+    pub(crate) control: Option<Point>,
+}
+
+impl PathNode {
+    fn control_in(&self) -> Option<Point> {
+        self.control.map(|control| Point {
+            x: 2.0 * self.point.x - control.x,
+            y: 2.0 * self.point.y - control.y,
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PathShape {
+    pub(crate) nodes: Vec<PathNode>,
+    pub(crate) closed: bool,
+    pub(crate) finished: bool,
+}
+
+impl PathShape {
+    const CLOSE_DISTANCE: f32 = 10.0;
+
+    //相邻两个节点组成一段，闭合时再加上首尾相连的一段
+    fn segments(&self) -> Vec<(PathNode, PathNode)> {
+        let mut segments = self
+            .nodes
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect::<Vec<_>>();
+
+        if self.closed {
+            if let (Some(last), Some(first)) = (self.nodes.last(), self.nodes.first()) {
+                segments.push((*last, *first));
+            }
+        }
+
+        segments
+    }
+
+    fn can_close_at(&self, point: Point) -> bool {
+        match self.nodes.first() {
+            Some(first) if self.nodes.len() >= 2 => {
+                first.point.distance(point) < PathShape::CLOSE_DISTANCE
+            }
+            _ => false,
+        }
+    }
+
+    fn translate(&mut self, x: f32, y: f32) {
+        translate_vertices(
+            self.nodes
+                .iter_mut()
+                .flat_map(|node| std::iter::once(&mut node.point).chain(node.control.as_mut())),
+            x,
+            y,
+        );
+    }
+
+    fn build(&self, builder: &mut Builder) {
+        if let Some(first) = self.nodes.first() {
+            builder.move_to(first.point);
+            for (from, to) in self.segments() {
+                match (from.control, to.control_in()) {
+                    (None, None) => builder.line_to(to.point),
+                    (control_a, control_b) => builder.bezier_curve_to(
+                        control_a.unwrap_or(from.point),
+                        control_b.unwrap_or(to.point),
+                        to.point,
+                    ),
+                }
+            }
+            if self.closed {
+                builder.close();
+            }
+        }
+    }
+}
+
+impl Shape for PathShape {
+    fn is_complete(&self) -> bool {
+        self.finished && self.nodes.len() >= 2
+    }
+    fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    fn points(&self) -> HashMap<String, Point> {
+        let mut points = HashMap::new();
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            points.insert(format!("node_{}", index), node.point);
+            if let (Some(control_in), Some(control_out)) = (node.control_in(), node.control) {
+                points.insert(format!("in_{}", index), control_in);
+                points.insert(format!("out_{}", index), control_out);
+            }
+        }
+
+        points
+    }
+    fn attributes(&self) -> HashMap<String, f32> {
+        let mut attrs = HashMap::new();
+
+        attrs.insert(String::from("nodes"), self.nodes.len() as f32);
+
+        attrs
+    }
+
+    fn update(&mut self, message: ShapeMessage) {
+        match message {
+            ShapeMessage::Labor(point) => {
+                if !self.finished {
+                    if self.can_close_at(point) {
+                        self.closed = true;
+                        self.finished = true;
+                    } else {
+                        self.nodes.push(PathNode {
+                            point,
+                            control: None,
+                        });
+                    }
+                }
+            }
+            ShapeMessage::LaborDrag(from, to) => {
+                if !self.finished {
+                    if self.can_close_at(from) {
+                        self.closed = true;
+                        self.finished = true;
+                    } else {
+                        self.nodes.push(PathNode {
+                            point: from,
+                            control: Some(to),
+                        });
+                    }
+                }
+            }
+            ShapeMessage::Finish => {
+                if self.nodes.len() >= 2 {
+                    self.finished = true;
+                }
+            }
+            ShapeMessage::MovePoint(index, point) => {
+                let parsed = index
+                    .split_once('_')
+                    .and_then(|(kind, i)| Some((kind, i.parse::<usize>().ok()?)));
+                if let Some((kind, i)) = parsed {
+                    if let Some(node) = self.nodes.get_mut(i) {
+                        match kind {
+                            //移动节点时控制柄跟着一起走
+                            "node" => {
+                                let (x, y) = (point.x - node.point.x, point.y - node.point.y);
+                                node.point = point;
+                                if let Some(control) = &mut node.control {
+                                    control.x += x;
+                                    control.y += y;
+                                }
+                            }
+                            "out" => {
+                                if node.control.is_some() {
+                                    node.control = Some(point);
+                                }
+                            }
+                            "in" if node.control.is_some() => {
+                                node.control = Some(Point {
+                                    x: 2.0 * node.point.x - point.x,
+                                    y: 2.0 * node.point.y - point.y,
+                                });
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            ShapeMessage::Move(index, point) => {
+                if let Some(p) = self.points().get(&index) {
+                    self.translate(point.x - p.x, point.y - p.y);
+                }
+            }
+            ShapeMessage::Centered(p) => {
+                if let Some(center) = bounding_center(self.nodes.iter().map(|node| node.point)) {
+                    self.translate(p.x - center.x, p.y - center.y);
+                }
+            }
+            ShapeMessage::Reset if !self.is_empty() => {
+                self.nodes.clear();
+                self.closed = false;
+                self.finished = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn preview(&self, cursor_position: Point) -> Option<Path> {
+        self.nodes.last().map(|last| {
+            Path::new(|builder| {
+                self.build(builder);
+                builder.move_to(last.point);
+                match last.control {
+                    Some(control) => {
+                        builder.bezier_curve_to(control, cursor_position, cursor_position)
+                    }
+                    None => builder.line_to(cursor_position),
+                }
+            })
+        })
+    }
+    fn draw(&self, selected: bool) -> (Option<Path>, Option<Path>) {
+        if self.is_complete() {
+            let to_fill = if selected {
+                Some(Path::new(|builder| {
+                    for (_, point) in self.points() {
+                        builder.circle(point, 5.0);
+                    }
+                }))
+            } else {
+                None
+            };
+            (
+                Some(Path::new(|builder| {
+                    self.build(builder);
+
+                    //光滑节点的两个控制柄连成一条线
+                    if selected {
+                        for node in self.nodes.iter() {
+                            if let (Some(control_in), Some(control_out)) =
+                                (node.control_in(), node.control)
+                            {
+                                builder.move_to(control_in);
+                                builder.line_to(control_out);
+                            }
+                        }
+                    }
+                })),
+                to_fill,
+            )
+        } else {
+            (None, None)
+        }
+    }
+    fn transform(&mut self, transform: &Transform) -> bool {
+        for node in self.nodes.iter_mut() {
+            transform_points(
+                std::iter::once(&mut node.point).chain(node.control.iter_mut()),
+                transform,
+            );
+        }
+        true
+    }
+    fn export_as_svg(&self) -> Option<Data> {
+        if self.is_complete() {
+            let first = self.nodes.first()?;
+            let data = self.segments().into_iter().fold(
+                Data::new().move_to((first.point.x, first.point.y)),
+                |acc, (from, to)| match (from.control, to.control_in()) {
+                    (None, None) => acc.line_to((to.point.x, to.point.y)),
+                    (control_a, control_b) => {
+                        let control_a = control_a.unwrap_or(from.point);
+                        let control_b = control_b.unwrap_or(to.point);
+                        acc.cubic_curve_to(vec![
+                            control_a.x,
+                            control_a.y,
+                            control_b.x,
+                            control_b.y,
+                            to.point.x,
+                            to.point.y,
+                        ])
+                    }
+                },
+            );
+            Some(if self.closed { data.close() } else { data })
+        } else {
+            None
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Polyline {
+    #[serde_as(as = "Vec<SerdePoint>")]
+    pub(crate) vertices: Vec<Point>,
+    pub(crate) finished: bool,
+}
+
+impl Shape for Polyline {
+    fn is_complete(&self) -> bool {
+        self.finished && self.vertices.len() >= 2
+    }
+    fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+    fn points(&self) -> HashMap<String, Point> {
+        vertices_points(&self.vertices)
+    }
+    fn attributes(&self) -> HashMap<String, f32> {
+        let mut attrs = HashMap::new();
+
+        if self.vertices.len() >= 2 {
+            attrs.insert(
+                String::from("length"),
+                self.vertices
+                    .windows(2)
+                    .map(|pair| pair[0].distance(pair[1]))
+                    .sum(),
+            );
+        }
+
+        attrs
+    }
+
+    fn update(&mut self, message: ShapeMessage) {
+        match message {
+            ShapeMessage::Labor(point) => {
+                if !self.finished {
+                    self.vertices.push(point);
+                }
+            }
+            ShapeMessage::Finish => {
+                if self.vertices.len() >= 2 {
+                    self.finished = true;
+                }
+            }
+            ShapeMessage::MovePoint(index, point) => move_vertex(&mut self.vertices, &index, point),
+            ShapeMessage::Move(index, point) => {
+                if let Some(p) = self.points().get(&index) {
+                    translate_vertices(&mut self.vertices, point.x - p.x, point.y - p.y);
+                }
+            }
+            ShapeMessage::Centered(p) => {
+                if let Some(center) = bounding_center(self.vertices.iter().copied()) {
+                    translate_vertices(&mut self.vertices, p.x - center.x, p.y - center.y);
+                }
+            }
+            ShapeMessage::Reset if !self.is_empty() => {
+                self.vertices.clear();
+                self.finished = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn preview(&self, cursor_position: Point) -> Option<Path> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(Path::new(|builder| {
+                build_vertices(builder, &self.vertices);
+                builder.line_to(cursor_position);
+            }))
+        }
+    }
+    fn draw(&self, selected: bool) -> (Option<Path>, Option<Path>) {
+        if self.is_complete() {
+            let to_fill = if selected {
+                Some(Path::new(|builder| {
+                    for point in self.vertices.iter() {
+                        builder.circle(*point, 5.0);
+                    }
+                }))
+            } else {
+                None
+            };
+            (
+                Some(Path::new(|builder| build_vertices(builder, &self.vertices))),
+                to_fill,
+            )
+        } else {
+            (None, None)
+        }
+    }
+    fn transform(&mut self, transform: &Transform) -> bool {
+        transform_points(self.vertices.iter_mut(), transform);
+        true
+    }
+    fn export_as_svg(&self) -> Option<Data> {
+        if self.is_complete() {
+            Some(export_vertices(&self.vertices))
+        } else {
+            None
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Polygon {
+    #[serde_as(as = "Vec<SerdePoint>")]
+    pub(crate) vertices: Vec<Point>,
+    pub(crate) finished: bool,
+}
+
+impl Shape for Polygon {
+    fn is_complete(&self) -> bool {
+        self.finished && self.vertices.len() >= 3
+    }
+    fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+    fn points(&self) -> HashMap<String, Point> {
+        vertices_points(&self.vertices)
+    }
+    fn attributes(&self) -> HashMap<String, f32> {
+        let mut attrs = HashMap::new();
+
+        if let (Some(first), Some(last)) = (self.vertices.first(), self.vertices.last()) {
+            if self.vertices.len() >= 3 {
+                let perimeter = self
+                    .vertices
+                    .windows(2)
+                    .map(|pair| pair[0].distance(pair[1]))
+                    .sum::<f32>()
+                    + last.distance(*first);
+                attrs.insert(String::from("perimeter"), perimeter);
+            }
+        }
+
+        attrs
+    }
+
+    fn update(&mut self, message: ShapeMessage) {
+        match message {
+            ShapeMessage::Labor(point) => {
+                if !self.finished {
+                    self.vertices.push(point);
+                }
+            }
+            ShapeMessage::Finish => {
+                if self.vertices.len() >= 3 {
+                    self.finished = true;
+                }
+            }
+            ShapeMessage::MovePoint(index, point) => move_vertex(&mut self.vertices, &index, point),
+            ShapeMessage::Move(index, point) => {
+                if let Some(p) = self.points().get(&index) {
+                    translate_vertices(&mut self.vertices, point.x - p.x, point.y - p.y);
+                }
+            }
+            ShapeMessage::Centered(p) => {
+                if let Some(center) = bounding_center(self.vertices.iter().copied()) {
+                    translate_vertices(&mut self.vertices, p.x - center.x, p.y - center.y);
+                }
+            }
+            ShapeMessage::Reset if !self.is_empty() => {
+                self.vertices.clear();
+                self.finished = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn preview(&self, cursor_position: Point) -> Option<Path> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(Path::new(|builder| {
+                build_vertices(builder, &self.vertices);
+                builder.line_to(cursor_position);
+                builder.close();
+            }))
+        }
+    }
+    fn draw(&self, selected: bool) -> (Option<Path>, Option<Path>) {
+        if self.is_complete() {
+            let to_fill = if selected {
+                Some(Path::new(|builder| {
+                    for point in self.vertices.iter() {
+                        builder.circle(*point, 5.0);
+                    }
+                }))
+            } else {
+                None
+            };
+            (
+                Some(Path::new(|builder| {
+                    build_vertices(builder, &self.vertices);
+                    builder.close();
+                })),
+                to_fill,
+            )
+        } else {
+            (None, None)
+        }
+    }
+    fn transform(&mut self, transform: &Transform) -> bool {
+        transform_points(self.vertices.iter_mut(), transform);
+        true
+    }
+    fn export_as_svg(&self) -> Option<Data> {
+        if self.is_complete() {
+            Some(export_vertices(&self.vertices).close())
+        } else {
+            None
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Star {
+    #[serde_as(as = "Option<SerdePoint>")]
+    pub(crate) center: Option<Point>,
+    //外接圆半径
+    pub(crate) radius: Option<f32>,
+    //第一个外顶点的方向，弧度，顺时针为正
+    pub(crate) rotation: f32,
+    pub(crate) sides: usize,
+    //内顶点半径和外顶点半径之比，为1时是正多边形
+    pub(crate) ratio: f32,
+}
+
+impl Default for Star {
+    fn default() -> Self {
+        Star::regular()
+    }
+}
+
+impl Star {
+    const MIN_SIDES: usize = 3;
+    const MAX_SIDES: usize = 100;
+
+    pub fn regular() -> Self {
+        Star {
+            center: None,
+            radius: None,
+            rotation: -PI / 2.0,
+            sides: 6,
+            ratio: 1.0,
+        }
+    }
+
+    //五角星，内外顶点交替
+    pub fn pointed() -> Self {
+        Star {
+            sides: 5,
+            ratio: 0.5,
+            ..Star::regular()
+        }
+    }
+
+    //外顶点和内顶点交替排列，ratio为1时只有外顶点
+    fn vertices(center: Point, radius: f32, rotation: f32, sides: usize, ratio: f32) -> Vec<Point> {
+        let step = PI / sides as f32;
+        let at = |angle: f32, r: f32| Point {
+            x: center.x + r * angle.cos(),
+            y: center.y + r * angle.sin(),
+        };
+
+        (0..sides)
+            .flat_map(|i| {
+                let angle = rotation + 2.0 * step * i as f32;
+                if ratio < 1.0 {
+                    vec![at(angle, radius), at(angle + step, radius * ratio)]
+                } else {
+                    vec![at(angle, radius)]
+                }
+            })
+            .collect()
+    }
+}
+
+impl Shape for Star {
+    fn is_complete(&self) -> bool {
+        self.center.is_some() && self.radius.is_some()
+    }
+    fn is_empty(&self) -> bool {
+        self.center.is_none() && self.radius.is_none()
+    }
+    fn points(&self) -> HashMap<String, Point> {
+        let mut points = HashMap::new();
+
+        if let Some(center) = self.center {
+            points.insert(String::from("center"), center);
+            if let Some(radius) = self.radius {
+                let (sin, cos) = self.rotation.sin_cos();
+                points.insert(
+                    String::from("vertex"),
+                    center + Vector::new(cos, sin) * radius,
+                );
+            }
+        }
+
+        points
+    }
+    fn attributes(&self) -> HashMap<String, f32> {
+        let mut attrs = HashMap::new();
+
+        if let Some(radius) = self.radius {
+            attrs.insert(String::from("radius"), radius);
+            attrs.insert(String::from("rotation"), self.rotation.to_degrees());
+            attrs.insert(String::from("sides"), self.sides as f32);
+            attrs.insert(String::from("ratio"), self.ratio);
+        }
+
+        attrs
+    }
+    fn is_editable(&self, attribute: &str) -> bool {
+        matches!(attribute, "radius" | "rotation" | "sides" | "ratio")
+    }
+
+    fn update(&mut self, message: ShapeMessage) {
+        match message {
+            ShapeMessage::Labor(point) => {
+                if self.center.is_none() {
+                    self.center = Some(point);
+                } else if let (Some(center), None) = (self.center, self.radius) {
+                    let offset = point - center;
+                    self.radius = Some(get_radius(center, point));
+                    self.rotation = offset.y.atan2(offset.x);
+                }
+            }
+            ShapeMessage::Attribute(name, value) => {
+                if let Ok(value) = value.parse::<f32>() {
+                    match name.as_str() {
+                        "radius" if value > 0.0 && self.radius.is_some() => {
+                            self.radius = Some(value)
+                        }
+                        "rotation" => self.rotation = value.to_radians(),
+                        "sides" => {
+                            self.sides =
+                                (value.round() as usize).clamp(Star::MIN_SIDES, Star::MAX_SIDES)
+                        }
+                        "ratio" if value > 0.0 => self.ratio = value.min(1.0),
+                        _ => {}
+                    }
+                }
+            }
+            ShapeMessage::MovePoint(index, point) => {
+                if let (Some(center), Some(radius)) = (&mut self.center, &mut self.radius) {
+                    match index.as_str() {
+                        "center" => *center = point,
+                        //拖动外顶点同时缩放和旋转
+                        "vertex" => {
+                            let offset = point - *center;
+                            *radius = get_radius(*center, point);
+                            self.rotation = offset.y.atan2(offset.x);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            ShapeMessage::Move(index, point) => {
+                if let Some(p) = self.points().get(&index) {
+                    if let Some(center) = &mut self.center {
+                        *center = *center + (point - *p);
+                    }
+                }
+            }
+            ShapeMessage::Centered(p) => {
+                if let Some(center) = &mut self.center {
+                    *center = p;
+                }
+            }
+            ShapeMessage::Reset if !self.is_empty() => {
+                self.center = None;
+                self.radius = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn preview(&self, cursor_position: Point) -> Option<Path> {
+        self.center.map(|center| {
+            let (radius, rotation) = match self.radius {
+                Some(radius) => (radius, self.rotation),
+                None => {
+                    let offset = cursor_position - center;
+                    (
+                        get_radius(center, cursor_position),
+                        offset.y.atan2(offset.x),
+                    )
+                }
+            };
+            Path::new(|builder| {
+                build_vertices(
+                    builder,
+                    &Star::vertices(center, radius, rotation, self.sides, self.ratio),
+                );
+                builder.close();
+            })
+        })
+    }
+    fn draw(&self, selected: bool) -> (Option<Path>, Option<Path>) {
+        if let (Some(center), Some(radius)) = (self.center, self.radius) {
+            let to_fill = if selected {
+                Some(Path::new(|builder| {
+                    for point in self.points().values() {
+                        builder.circle(*point, 5.0);
+                    }
+                }))
+            } else {
+                None
+            };
+            (
+                Some(Path::new(|builder| {
+                    build_vertices(
+                        builder,
+                        &Star::vertices(center, radius, self.rotation, self.sides, self.ratio),
+                    );
+                    builder.close();
+                })),
+                to_fill,
+            )
+        } else {
+            (None, None)
+        }
+    }
+    fn export_as_svg(&self) -> Option<Data> {
+        if let (Some(center), Some(radius)) = (self.center, self.radius) {
+            Some(
+                export_vertices(&Star::vertices(
+                    center,
+                    radius,
+                    self.rotation,
+                    self.sides,
+                    self.ratio,
+                ))
+                .close(),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Freehand {
+    //绘制过程中是采样点，结束之后是简化过的顶点
+    #[serde_as(as = "Vec<SerdePoint>")]
+    pub(crate) vertices: Vec<Point>,
+    pub(crate) tolerance: f32,
+    pub(crate) finished: bool,
+}
+
+impl Default for Freehand {
+    fn default() -> Self {
+        Freehand {
+            vertices: vec![],
+            tolerance: Freehand::DEFAULT_TOLERANCE,
+            finished: false,
+        }
+    }
+}
+
+impl Freehand {
+    pub const DEFAULT_TOLERANCE: f32 = 2.0;
+    pub const TOLERANCE_RANGE: std::ops::RangeInclusive<f32> = 0.5..=20.0;
+
+    //经过相邻顶点的中点，顶点本身作为二次贝塞尔曲线的控制点，使折线变得平滑
+    fn build(builder: &mut Builder, vertices: &[Point]) {
+        if let (Some(first), Some(last)) = (vertices.first(), vertices.last()) {
+            builder.move_to(*first);
+            for pair in vertices.windows(2).skip(1) {
+                builder.quadratic_curve_to(pair[0], midpoint(pair[0], pair[1]));
+            }
+            builder.line_to(*last);
+        }
+    }
+}
+
+impl Shape for Freehand {
+    fn is_complete(&self) -> bool {
+        self.finished && self.vertices.len() >= 2
+    }
+    fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+    fn points(&self) -> HashMap<String, Point> {
+        if self.finished {
+            vertices_points(&self.vertices)
+        } else {
+            HashMap::new()
+        }
+    }
+    fn attributes(&self) -> HashMap<String, f32> {
+        HashMap::new()
+    }
+
+    fn update(&mut self, message: ShapeMessage) {
+        match message {
+            ShapeMessage::Labor(point) | ShapeMessage::Sample(point) => {
+                if !self.finished {
+                    self.vertices.push(point);
+                }
+            }
+            ShapeMessage::Finish => {
+                //只点了一下没有拖动的话什么都不画
+                if self.vertices.len() < 2 {
+                    self.vertices.clear();
+                } else if !self.finished {
+                    self.vertices = simplify(&self.vertices, self.tolerance);
+                    self.finished = true;
+                }
+            }
+            ShapeMessage::Tolerance(tolerance) => {
+                if Freehand::TOLERANCE_RANGE.contains(&tolerance) {
+                    self.tolerance = tolerance;
+                }
+            }
+            ShapeMessage::MovePoint(index, point) => move_vertex(&mut self.vertices, &index, point),
+            ShapeMessage::Move(index, point) => {
+                if let Some(p) = self.points().get(&index) {
+                    translate_vertices(&mut self.vertices, point.x - p.x, point.y - p.y);
+                }
+            }
+            ShapeMessage::Centered(p) => {
+                if let Some(center) = bounding_center(self.vertices.iter().copied()) {
+                    translate_vertices(&mut self.vertices, p.x - center.x, p.y - center.y);
+                }
+            }
+            ShapeMessage::Reset if !self.is_empty() => {
+                self.vertices.clear();
+                self.finished = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn preview(&self, cursor_position: Point) -> Option<Path> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(Path::new(|builder| {
+                build_vertices(builder, &self.vertices);
+                builder.line_to(cursor_position);
+            }))
+        }
+    }
+    fn draw(&self, selected: bool) -> (Option<Path>, Option<Path>) {
+        if self.is_complete() {
+            let to_fill = if selected {
+                Some(Path::new(|builder| {
+                    for point in self.vertices.iter() {
+                        builder.circle(*point, 5.0);
+                    }
+                }))
+            } else {
+                None
+            };
+            (
+                Some(Path::new(|builder| {
+                    Freehand::build(builder, &self.vertices)
+                })),
+                to_fill,
+            )
+        } else {
+            (None, None)
+        }
+    }
+    fn transform(&mut self, transform: &Transform) -> bool {
+        transform_points(self.vertices.iter_mut(), transform);
+        true
+    }
+    fn export_as_svg(&self) -> Option<Data> {
+        if !self.is_complete() {
+            return None;
+        }
+
+        //只保留两位小数以减小导出的体积
+        let round = |p: Point| ((p.x * 100.0).round() / 100.0, (p.y * 100.0).round() / 100.0);
+        let (first, last) = (self.vertices.first()?, self.vertices.last()?);
+        let data = self.vertices.windows(2).skip(1).fold(
+            Data::new().move_to(round(*first)),
+            |acc, pair| {
+                let (control, to) = (round(pair[0]), round(midpoint(pair[0], pair[1])));
+                acc.quadratic_curve_to(vec![control.0, control.1, to.0, to.1])
+            },
+        );
+        Some(data.line_to(round(*last)))
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextFont {
+    #[default]
+    Default,
+    FiraCode,
+}
+
+impl TextFont {
+    pub const ALL: [TextFont; 2] = [TextFont::Default, TextFont::FiraCode];
+
+    fn family(&self) -> &str {
+        match self {
+            TextFont::Default => "sans-serif",
+            TextFont::FiraCode => "Fira Code, monospace",
+        }
+    }
+}
+
+impl From<TextFont> for Font {
+    fn from(font: TextFont) -> Font {
+        match font {
+            TextFont::Default => Font::Default,
+            TextFont::FiraCode => FIRA_CODE,
+        }
+    }
+}
+
+impl Display for TextFont {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TextFont::Default => "default",
+                TextFont::FiraCode => "Fira Code",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl TextAlign {
+    pub const ALL: [TextAlign; 3] = [TextAlign::Left, TextAlign::Center, TextAlign::Right];
+
+    fn anchor(&self) -> &str {
+        match self {
+            TextAlign::Left => "start",
+            TextAlign::Center => "middle",
+            TextAlign::Right => "end",
+        }
+    }
+}
+
+impl From<TextAlign> for Horizontal {
+    fn from(align: TextAlign) -> Horizontal {
+        match align {
+            TextAlign::Left => Horizontal::Left,
+            TextAlign::Center => Horizontal::Center,
+            TextAlign::Right => Horizontal::Right,
+        }
+    }
+}
+
+impl Display for TextAlign {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TextAlign::Left => "left",
+                TextAlign::Center => "center",
+                TextAlign::Right => "right",
+            }
+        )
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Text {
+    //文字的锚点，垂直方向上是文字的顶部，水平方向上的含义由align决定
+    #[serde_as(as = "Option<SerdePoint>")]
+    pub(crate) position: Option<Point>,
+    pub(crate) content: String,
+    pub(crate) size: f32,
+    pub(crate) font: TextFont,
+    pub(crate) align: TextAlign,
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Text {
+            position: None,
+            content: String::from("Text"),
+            size: 20.0,
+            font: TextFont::default(),
+            align: TextAlign::default(),
+        }
+    }
+}
+
+impl Text {
+    //画布上量不了字宽，按每个字符0.6倍字号估算
+    const CHAR_WIDTH: f32 = 0.6;
+
+    //估算的文字框，包围盒、框选和控制框都用它
+    pub fn outline(&self) -> Option<Data> {
+        let position = self.position?;
+        let lines = self.content.lines();
+        let chars = lines
+            .clone()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let width = chars as f32 * self.size * Text::CHAR_WIDTH;
+        let height = lines.count().max(1) as f32 * self.size;
+        let left = match self.align {
+            TextAlign::Left => position.x,
+            TextAlign::Center => position.x - width / 2.0,
+            TextAlign::Right => position.x - width,
+        };
+        Some(
+            Data::new()
+                .move_to((left, position.y))
+                .line_to((left + width, position.y))
+                .line_to((left + width, position.y + height))
+                .line_to((left, position.y + height))
+                .close(),
+        )
+    }
+
+    pub fn export_as_svg_text(&self) -> Option<SvgText> {
+        self.position.map(|position| {
+            SvgText::new()
+                .set("x", position.x)
+                .set("y", position.y)
+                .set("font-size", self.size)
+                .set("font-family", self.font.family())
+                .set("text-anchor", self.align.anchor())
+                .set("dominant-baseline", "hanging")
+                .add(TextNode::new(escape_xml(&self.content)))
+        })
+    }
+}
+
+impl Shape for Text {
+    fn is_complete(&self) -> bool {
+        self.position.is_some()
+    }
+    fn is_empty(&self) -> bool {
+        self.position.is_none()
+    }
+    fn points(&self) -> HashMap<String, Point> {
+        let mut points = HashMap::new();
+
+        if let Some(position) = self.position {
+            points.insert(String::from("position"), position);
+        }
+
+        points
+    }
+    fn attributes(&self) -> HashMap<String, f32> {
+        HashMap::new()
+    }
+
+    fn update(&mut self, message: ShapeMessage) {
+        match message {
+            ShapeMessage::Labor(point) => {
+                if self.position.is_none() {
+                    self.position = Some(point);
+                }
+            }
+            ShapeMessage::MovePoint(_, point)
+            | ShapeMessage::Move(_, point)
+            | ShapeMessage::Centered(point) => {
+                if let Some(position) = &mut self.position {
+                    *position = point;
+                }
+            }
+            ShapeMessage::TextContent(content) => self.content = content,
+            ShapeMessage::TextSize(size) => {
+                if let Ok(size) = size.parse::<f32>() {
+                    if size > 0.0 {
+                        self.size = size;
+                    }
+                }
+            }
+            ShapeMessage::TextFont(font) => self.font = font,
+            ShapeMessage::TextAlign(align) => self.align = align,
+            ShapeMessage::Reset if !self.is_empty() => {
+                self.position = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn preview(&self, _cursor_position: Point) -> Option<Path> {
+        None
+    }
+    fn draw(&self, selected: bool) -> (Option<Path>, Option<Path>) {
+        match self.position {
+            Some(position) if selected => (None, Some(Path::circle(position, 5.0))),
+            _ => (None, None),
+        }
+    }
+    //画布上的字不能旋转和斜切，只有平移和等比放大缩小直接改位置和字号
+    fn transform(&mut self, transform: &Transform) -> bool {
+        let upright = transform.b.abs() < 1e-4 && transform.c.abs() < 1e-4 && transform.a > 0.0;
+        match transform.uniform_scale() {
+            Some(scale) if upright => {
+                transform_points(self.position.iter_mut(), transform);
+                self.size *= scale;
+                true
+            }
+            _ => false,
+        }
+    }
+    fn text(&self) -> Option<CanvasText> {
+        self.position.map(|position| CanvasText {
+            content: self.content.clone(),
+            position,
+            size: self.size,
+            font: self.font.into(),
+            horizontal_alignment: self.align.into(),
+            ..CanvasText::default()
+        })
+    }
+    fn export_as_svg(&self) -> Option<Data> {
+        None
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Marker {
+    #[default]
+    None,
+    Triangle,
+    OpenTriangle,
+    Circle,
+    Diamond,
+}
+
+impl Marker {
+    pub const ALL: [Marker; 5] = [
+        Marker::None,
+        Marker::Triangle,
+        Marker::OpenTriangle,
+        Marker::Circle,
+        Marker::Diamond,
+    ];
+
+    //空心三角形用描边，其他的用填充
+    pub fn is_filled(&self) -> bool {
+        !matches!(self, Marker::OpenTriangle)
+    }
+
+    //tip是线段的端点，direction是端点处朝外的单位向量
+    fn path(&self, tip: Point, direction: Vector, size: f32) -> Option<Path> {
+        let normal = Vector::new(-direction.y, direction.x);
+        match self {
+            Marker::None => None,
+            Marker::Triangle => Some(Path::new(|builder| {
+                let base = tip + direction * -size;
+                builder.move_to(tip);
+                builder.line_to(base + normal * (size / 2.0));
+                builder.line_to(base + normal * (-size / 2.0));
+                builder.close();
+            })),
+            Marker::OpenTriangle => Some(Path::new(|builder| {
+                let base = tip + direction * -size;
+                builder.move_to(base + normal * (size / 2.0));
+                builder.line_to(tip);
+                builder.line_to(base + normal * (-size / 2.0));
+            })),
+            Marker::Circle => Some(Path::circle(tip, size / 2.0)),
+            Marker::Diamond => Some(Path::new(|builder| {
+                builder.move_to(tip + direction * (size / 2.0));
+                builder.line_to(tip + normal * (size / 2.0));
+                builder.line_to(tip + direction * (-size / 2.0));
+                builder.line_to(tip + normal * (-size / 2.0));
+                builder.close();
+            })),
+        }
+    }
+
+    //在10x10的坐标系里朝x轴正方向，refX对应端点所在的位置
+    //颜色里不带透明度，透明度单独设置，否则半透明的线会有不透明的箭头
+    pub fn export_as_svg(&self, id: &str, fill: &str, opacity: f32) -> Option<SvgMarker> {
+        let (content, ref_x): (Element, f32) = match self {
+            Marker::None => return None,
+            Marker::Triangle => (
+                SvgPath::new()
+                    .set("d", "M 0 0 L 10 5 L 0 10 Z")
+                    .set("fill", fill)
+                    .set("fill-opacity", opacity)
+                    .into(),
+                10.0,
+            ),
+            Marker::OpenTriangle => (
+                SvgPath::new()
+                    .set("d", "M 0 0 L 10 5 L 0 10")
+                    .set("fill", "none")
+                    .set("stroke", fill)
+                    .set("stroke-opacity", opacity)
+                    .into(),
+                10.0,
+            ),
+            Marker::Circle => (
+                SvgCircle::new()
+                    .set("cx", 5)
+                    .set("cy", 5)
+                    .set("r", 5)
+                    .set("fill", fill)
+                    .set("fill-opacity", opacity)
+                    .into(),
+                5.0,
+            ),
+            Marker::Diamond => (
+                SvgPath::new()
+                    .set("d", "M 0 5 L 5 0 L 10 5 L 5 10 Z")
+                    .set("fill", fill)
+                    .set("fill-opacity", opacity)
+                    .into(),
+                5.0,
+            ),
+        };
+
+        Some(
+            SvgMarker::new()
+                .set("id", id)
+                .set("viewBox", "0 0 10 10")
+                .set("refX", ref_x)
+                .set("refY", 5)
+                .set("markerUnits", "strokeWidth")
+                .set("markerWidth", Arrow::MARKER_SCALE)
+                .set("markerHeight", Arrow::MARKER_SCALE)
+                .set("orient", "auto-start-reverse")
+                .add(content),
+        )
+    }
+}
+
+impl Display for Marker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Marker::None => "none",
+                Marker::Triangle => "triangle",
+                Marker::OpenTriangle => "open triangle",
+                Marker::Circle => "circle",
+                Marker::Diamond => "diamond",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ArrowBody {
+    Line(Line),
+    QuadraticBezier(QuadraticBezier),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Arrow {
+    pub(crate) body: ArrowBody,
+    pub(crate) start: Marker,
+    pub(crate) end: Marker,
+}
+
+impl Default for Arrow {
+    fn default() -> Self {
+        Arrow::straight()
+    }
+}
+
+impl Arrow {
+    //箭头的大小是线宽的倍数
+    pub const MARKER_SCALE: f32 = 4.0;
+
+    pub fn straight() -> Self {
+        Arrow {
+            body: ArrowBody::Line(Line::default()),
+            start: Marker::None,
+            end: Marker::Triangle,
+        }
+    }
+
+    pub fn curved() -> Self {
+        Arrow {
+            body: ArrowBody::QuadraticBezier(QuadraticBezier::default()),
+            ..Arrow::straight()
+        }
+    }
+
+    fn body(&self) -> &dyn Shape {
+        match &self.body {
+            ArrowBody::Line(line) => line,
+            ArrowBody::QuadraticBezier(bezier) => bezier,
+        }
+    }
+
+    fn body_mut(&mut self) -> &mut dyn Shape {
+        match &mut self.body {
+            ArrowBody::Line(line) => line,
+            ArrowBody::QuadraticBezier(bezier) => bezier,
+        }
+    }
+
+    //两个端点以及端点处朝外的方向，曲线的方向由端点和控制点决定
+    fn ends(&self) -> Option<((Point, Vector), (Point, Vector))> {
+        let (start, start_toward, end, end_toward) = match self.body {
+            ArrowBody::Line(Line {
+                from: Some(from),
+                to: Some(to),
+            }) => (from, to, to, from),
+            ArrowBody::QuadraticBezier(QuadraticBezier {
+                a: Some(a),
+                b: Some(b),
+                control: Some(control),
+            }) => (a, control, b, control),
+            _ => return None,
+        };
+
+        let normalize = |v: Vector| {
+            let length = (v.x * v.x + v.y * v.y).sqrt();
+            if length > 0.0 {
+                Some(v * (1.0 / length))
+            } else {
+                None
+            }
+        };
+
+        Some((
+            (
+                start,
+                normalize(start - start_toward).or_else(|| normalize(start - end))?,
+            ),
+            (
+                end,
+                normalize(end - end_toward).or_else(|| normalize(end - start))?,
+            ),
+        ))
+    }
+
+    pub fn markers(&self, width: f32) -> Vec<(Marker, Path)> {
+        let size = width * Arrow::MARKER_SCALE;
+        match self.ends() {
+            Some(((start, start_direction), (end, end_direction))) => [
+                (self.start, self.start.path(start, start_direction, size)),
+                (self.end, self.end.path(end, end_direction, size)),
+            ]
+            .into_iter()
+            .filter_map(|(marker, path)| path.map(|path| (marker, path)))
+            .collect(),
+            None => vec![],
+        }
+    }
+}
+
+impl Shape for Arrow {
+    fn is_complete(&self) -> bool {
+        self.body().is_complete()
+    }
+    fn is_empty(&self) -> bool {
+        self.body().is_empty()
+    }
+    fn points(&self) -> HashMap<String, Point> {
+        self.body().points()
+    }
+    fn attributes(&self) -> HashMap<String, f32> {
+        self.body().attributes()
+    }
+
+    fn update(&mut self, message: ShapeMessage) {
+        match message {
+            ShapeMessage::StartMarker(marker) => self.start = marker,
+            ShapeMessage::EndMarker(marker) => self.end = marker,
+            message => self.body_mut().update(message),
+        }
+    }
+
+    fn preview(&self, cursor_position: Point) -> Option<Path> {
+        self.body().preview(cursor_position)
+    }
+    fn draw(&self, selected: bool) -> (Option<Path>, Option<Path>) {
+        self.body().draw(selected)
+    }
+    fn transform(&mut self, transform: &Transform) -> bool {
+        self.body_mut().transform(transform)
+    }
+    fn export_as_svg(&self) -> Option<Data> {
+        self.body().export_as_svg()
+    }
+}
+
+//以下是点数不定的图形共用的一些操作，顶点的索引形如"vertex_0"
+fn vertices_points(vertices: &[Point]) -> HashMap<String, Point> {
+    vertices
+        .iter()
+        .enumerate()
+        .map(|(index, point)| (format!("vertex_{}", index), *point))
+        .collect()
+}
+
+fn move_vertex(vertices: &mut [Point], index: &str, point: Point) {
+    if let Some(vertex) = index
+        .strip_prefix("vertex_")
+        .and_then(|i| i.parse::<usize>().ok())
+        .and_then(|i| vertices.get_mut(i))
+    {
+        *vertex = point;
+    }
+}
+
+fn translate_vertices<'a>(vertices: impl IntoIterator<Item = &'a mut Point>, x: f32, y: f32) {
+    for vertex in vertices {
+        vertex.x += x;
+        vertex.y += y;
+    }
+}
+
+fn transform_points<'a>(points: impl IntoIterator<Item = &'a mut Point>, transform: &Transform) {
+    for point in points {
+        *point = transform.apply(*point);
+    }
+}
+
+fn bounding_center(points: impl Iterator<Item = Point> + Clone) -> Option<Point> {
+    let min_x = points.clone().map(|p| p.x).reduce(f32::min)?;
+    let max_x = points.clone().map(|p| p.x).reduce(f32::max)?;
+    let min_y = points.clone().map(|p| p.y).reduce(f32::min)?;
+    let max_y = points.map(|p| p.y).reduce(f32::max)?;
+
+    Some(Point {
+        x: (min_x + max_x) / 2.0,
+        y: (min_y + max_y) / 2.0,
+    })
+}
+
+fn build_vertices(builder: &mut Builder, vertices: &[Point]) {
+    for (index, point) in vertices.iter().enumerate() {
+        if index == 0 {
+            builder.move_to(*point);
+        } else {
+            builder.line_to(*point);
+        }
+    }
+}
+
+fn export_vertices(vertices: &[Point]) -> Data {
+    vertices
+        .iter()
+        .enumerate()
+        .fold(Data::new(), |acc, (index, point)| {
+            if index == 0 {
+                acc.move_to((point.x, point.y))
+            } else {
+                acc.line_to((point.x, point.y))
+            }
+        })
+}
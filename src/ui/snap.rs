@@ -0,0 +1,195 @@
+use iced::{
+    canvas::{Frame, LineDash, Path, Stroke},
+    Color, Point, Rectangle,
+};
+
+#[derive(Debug, Clone)]
+pub enum SnapMessage {
+    GridToggled(bool),
+    SnapToGridToggled(bool),
+    SnapToPointsToggled(bool),
+    InputSpacing(String),
+}
+
+//对齐参考线的两个端点
+pub type Guide = (Point, Point);
+
+//网格和吸附的设置，只影响画布上的操作，不保存到文档里
+#[derive(Debug, Clone, Copy)]
+pub struct Snap {
+    pub grid: bool,
+    pub spacing: f32,
+    pub to_grid: bool,
+    //吸附到其余曲线的点、直线段的中点和中心，并显示对齐参考线
+    pub to_points: bool,
+}
+
+impl Default for Snap {
+    fn default() -> Self {
+        Snap {
+            grid: false,
+            spacing: 20.0,
+            to_grid: false,
+            to_points: false,
+        }
+    }
+}
+
+impl Snap {
+    //网格线在画布上至少隔开这么远，太密时隔一条画一条
+    const MIN_GRID_GAP: f32 = 8.0;
+
+    pub fn update(&mut self, message: SnapMessage) {
+        match message {
+            SnapMessage::GridToggled(grid) => self.grid = grid,
+            SnapMessage::SnapToGridToggled(to_grid) => self.to_grid = to_grid,
+            SnapMessage::SnapToPointsToggled(to_points) => self.to_points = to_points,
+            SnapMessage::InputSpacing(spacing) => {
+                if let Ok(spacing) = spacing.parse::<f32>() {
+                    if spacing > 0.0 {
+                        self.spacing = spacing;
+                    }
+                }
+            }
+        }
+    }
+
+    //离得足够近时先吸附到点上，否则x和y分别对齐到点或者网格上
+    pub fn snap(&self, point: Point, targets: &[Point], tolerance: f32) -> (Point, Vec<Guide>) {
+        if self.to_points {
+            let nearest = targets
+                .iter()
+                .filter(|target| target.distance(point) < tolerance)
+                .min_by(|a, b| a.distance(point).total_cmp(&b.distance(point)));
+            if let Some(target) = nearest {
+                return (*target, vec![]);
+            }
+        }
+
+        let align = |value: fn(&Point) -> f32| {
+            targets
+                .iter()
+                .filter(|target| {
+                    self.to_points && (value(target) - value(&point)).abs() < tolerance
+                })
+                .min_by(|a, b| {
+                    (value(a) - value(&point))
+                        .abs()
+                        .total_cmp(&(value(b) - value(&point)).abs())
+                })
+                .copied()
+        };
+        let grid = |value: f32| {
+            if self.to_grid {
+                (value / self.spacing).round() * self.spacing
+            } else {
+                value
+            }
+        };
+        let (x, y) = (align(|p| p.x), align(|p| p.y));
+        let snapped = Point::new(
+            x.map_or_else(|| grid(point.x), |x| x.x),
+            y.map_or_else(|| grid(point.y), |y| y.y),
+        );
+
+        let guides = x
+            .map(|x| (x, Point::new(x.x, snapped.y)))
+            .into_iter()
+            .chain(y.map(|y| (y, Point::new(snapped.x, y.y))))
+            .collect();
+        (snapped, guides)
+    }
+
+    //只画看得到的区域，area是文档里的坐标
+    pub fn draw_grid(&self, frame: &mut Frame, area: Rectangle, zoom: f32) {
+        let mut step = self.spacing;
+        while step * zoom < Snap::MIN_GRID_GAP {
+            step *= 2.0;
+        }
+
+        let path = Path::new(|builder| {
+            let mut x = (area.x / step).floor() * step;
+            while x <= area.x + area.width {
+                builder.move_to(Point::new(x, area.y));
+                builder.line_to(Point::new(x, area.y + area.height));
+                x += step;
+            }
+            let mut y = (area.y / step).floor() * step;
+            while y <= area.y + area.height {
+                builder.move_to(Point::new(area.x, y));
+                builder.line_to(Point::new(area.x + area.width, y));
+                y += step;
+            }
+        });
+        frame.stroke(
+            &path,
+            Stroke::default().with_color(Color::from_rgba(0.0, 0.0, 0.0, 0.1)),
+        );
+    }
+
+    pub fn draw_guides(frame: &mut Frame, guides: &[Guide], zoom: f32) {
+        let path = Path::new(|builder| {
+            for (from, to) in guides {
+                builder.move_to(*from);
+                builder.line_to(*to);
+            }
+        });
+        let segments = [4.0 / zoom, 4.0 / zoom];
+        frame.stroke(
+            &path,
+            Stroke {
+                color: Color::from_rgb(1.0, 0.0, 1.0),
+                width: 1.0,
+                line_dash: LineDash {
+                    segments: &segments,
+                    offset: 0,
+                },
+                ..Stroke::default()
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snap(to_grid: bool, to_points: bool) -> Snap {
+        Snap {
+            to_grid,
+            to_points,
+            ..Snap::default()
+        }
+    }
+
+    #[test]
+    fn nothing_enabled_keeps_the_point() {
+        let point = Point::new(13.0, 27.0);
+        let (snapped, guides) = snap(false, false).snap(point, &[Point::new(12.0, 26.0)], 5.0);
+        assert_eq!(snapped, point);
+        assert!(guides.is_empty());
+    }
+
+    #[test]
+    fn snaps_to_the_nearest_point() {
+        let targets = [Point::new(10.0, 10.0), Point::new(14.0, 14.0)];
+        let (snapped, guides) = snap(true, true).snap(Point::new(13.0, 13.0), &targets, 5.0);
+        assert_eq!(snapped, Point::new(14.0, 14.0));
+        assert!(guides.is_empty());
+    }
+
+    #[test]
+    fn rounds_to_the_grid() {
+        let (snapped, _) = snap(true, false).snap(Point::new(29.0, 51.0), &[], 5.0);
+        assert_eq!(snapped, Point::new(20.0, 60.0));
+    }
+
+    #[test]
+    fn aligns_each_axis_with_guides() {
+        //x对齐到点上，y没有可以对齐的点就落到网格上
+        let targets = [Point::new(102.0, 300.0)];
+        let (snapped, guides) = snap(true, true).snap(Point::new(100.0, 49.0), &targets, 5.0);
+        assert_eq!(snapped, Point::new(102.0, 40.0));
+        assert_eq!(guides, vec![(targets[0], snapped)]);
+    }
+}
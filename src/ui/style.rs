@@ -45,6 +45,8 @@ pub enum Button {
     Confirm,
     PreviewNavigator,
     Delete,
+    //色板里的色块
+    Swatch(Color),
 }
 
 //可选：background, shadow_offset, border_radius, border_width, border_color, text_color
@@ -77,6 +79,12 @@ impl button::StyleSheet for Button {
                 border_radius: 5.0,
                 ..button::Style::default()
             },
+            Button::Swatch(color) => button::Style {
+                background: Some(Background::Color(*color)),
+                border_width: 1.0,
+                border_color: Color::BLACK,
+                ..button::Style::default()
+            },
         }
     }
 
@@ -88,9 +96,16 @@ impl button::StyleSheet for Button {
     }
 
     fn hovered(&self) -> button::Style {
-        button::Style {
-            background: Some(Background::Color(HOVERED)),
-            ..self.active()
+        match self {
+            //悬停时保留色块本身的颜色
+            Button::Swatch(_) => button::Style {
+                border_width: 2.0,
+                ..self.active()
+            },
+            _ => button::Style {
+                background: Some(Background::Color(HOVERED)),
+                ..self.active()
+            },
         }
     }
 
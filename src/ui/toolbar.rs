@@ -3,7 +3,7 @@ use iced::pure::Element;
 use iced::{Alignment, Length};
 
 use super::icons;
-use super::shape::*;
+use super::shape::{self, *};
 use super::style;
 use super::EditMessage;
 
@@ -43,7 +43,7 @@ impl Toolbar {
                 Some(ToolbarMessage::Export),
             ))
             .push(button(
-                icons::rectangle(),
+                icons::line(),
                 "line",
                 style::Button::Toolbar,
                 Some(ToolbarMessage::Edit(EditMessage::ChangeShape(
@@ -67,13 +67,21 @@ impl Toolbar {
                 ))),
             ))
             .push(button(
-                icons::triangle(),
+                icons::circle(),
                 "circle",
                 style::Button::Toolbar,
                 Some(ToolbarMessage::Edit(EditMessage::ChangeShape(
                     Circle::default().into(),
                 ))),
             ))
+            .push(button(
+                icons::ellipse(),
+                "ellipse",
+                style::Button::Toolbar,
+                Some(ToolbarMessage::Edit(EditMessage::ChangeShape(
+                    Ellipse::default().into(),
+                ))),
+            ))
             .push(button(
                 icons::quadratic_bezier(),
                 "Bezier",
@@ -82,6 +90,94 @@ impl Toolbar {
                     QuadraticBezier::default().into(),
                 ))),
             ))
+            .push(button(
+                icons::cubic_bezier(),
+                "Cubic",
+                style::Button::Toolbar,
+                Some(ToolbarMessage::Edit(EditMessage::ChangeShape(
+                    CubicBezier::default().into(),
+                ))),
+            ))
+            .push(button(
+                icons::arc(),
+                "arc",
+                style::Button::Toolbar,
+                Some(ToolbarMessage::Edit(EditMessage::ChangeShape(
+                    shape::Arc::default().into(),
+                ))),
+            ))
+            .push(button(
+                icons::path(),
+                "path",
+                style::Button::Toolbar,
+                Some(ToolbarMessage::Edit(EditMessage::ChangeShape(
+                    PathShape::default().into(),
+                ))),
+            ))
+            .push(button(
+                icons::polyline(),
+                "polyline",
+                style::Button::Toolbar,
+                Some(ToolbarMessage::Edit(EditMessage::ChangeShape(
+                    Polyline::default().into(),
+                ))),
+            ))
+            .push(button(
+                icons::polygon(),
+                "polygon",
+                style::Button::Toolbar,
+                Some(ToolbarMessage::Edit(EditMessage::ChangeShape(
+                    Polygon::default().into(),
+                ))),
+            ))
+            .push(button(
+                icons::regular_polygon(),
+                "regular polygon",
+                style::Button::Toolbar,
+                Some(ToolbarMessage::Edit(EditMessage::ChangeShape(
+                    Star::regular().into(),
+                ))),
+            ))
+            .push(button(
+                icons::star(),
+                "star",
+                style::Button::Toolbar,
+                Some(ToolbarMessage::Edit(EditMessage::ChangeShape(
+                    Star::pointed().into(),
+                ))),
+            ))
+            .push(button(
+                icons::pencil(),
+                "pencil",
+                style::Button::Toolbar,
+                Some(ToolbarMessage::Edit(EditMessage::ChangeShape(
+                    Freehand::default().into(),
+                ))),
+            ))
+            .push(button(
+                icons::text(),
+                "text",
+                style::Button::Toolbar,
+                Some(ToolbarMessage::Edit(EditMessage::ChangeShape(
+                    shape::Text::default().into(),
+                ))),
+            ))
+            .push(button(
+                icons::up_arrow(),
+                "arrow",
+                style::Button::Toolbar,
+                Some(ToolbarMessage::Edit(EditMessage::ChangeShape(
+                    Arrow::straight().into(),
+                ))),
+            ))
+            .push(button(
+                icons::curved_arrow(),
+                "curved arrow",
+                style::Button::Toolbar,
+                Some(ToolbarMessage::Edit(EditMessage::ChangeShape(
+                    Arrow::curved().into(),
+                ))),
+            ))
             .push(button(
                 icons::delete(),
                 "clear",
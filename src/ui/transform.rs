@@ -0,0 +1,492 @@
+use iced::{
+    canvas::{Frame, Path, Stroke},
+    Color, Point, Rectangle, Vector,
+};
+use serde::{Deserialize, Serialize};
+use svg::node::element::path::{Command, Data};
+
+use super::utils::arc_to_cubics;
+
+#[derive(Debug, Clone)]
+pub enum TransformMessage {
+    InputAngle(String),
+    InputScaleX(String),
+    InputScaleY(String),
+    InputSkewX(String),
+    InputSkewY(String),
+    //按输入的数值变换选中的曲线
+    Apply,
+    FlipHorizontal,
+    FlipVertical,
+    //画布上拖动控制框得到的变换
+    Dragged(Transform),
+}
+
+//和svg的matrix(a b c d e f)对应，把(x, y)变成(ax + cy + e, bx + dy + f)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        e: 0.0,
+        f: 0.0,
+    };
+
+    pub fn translate(offset: Vector) -> Self {
+        Transform {
+            e: offset.x,
+            f: offset.y,
+            ..Transform::IDENTITY
+        }
+    }
+
+    //角度都用度数，y轴朝下所以正的角度是顺时针
+    pub fn rotate(degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Transform {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            ..Transform::IDENTITY
+        }
+    }
+
+    pub fn scale(x: f32, y: f32) -> Self {
+        Transform {
+            a: x,
+            d: y,
+            ..Transform::IDENTITY
+        }
+    }
+
+    pub fn skew(x: f32, y: f32) -> Self {
+        Transform {
+            b: y.to_radians().tan(),
+            c: x.to_radians().tan(),
+            ..Transform::IDENTITY
+        }
+    }
+
+    //先做自己再做next
+    pub fn then(&self, next: &Transform) -> Transform {
+        Transform {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            e: next.a * self.e + next.c * self.f + next.e,
+            f: next.b * self.e + next.d * self.f + next.f,
+        }
+    }
+
+    //以pivot为中心做变换，pivot的位置不变
+    pub fn about(&self, pivot: Point) -> Transform {
+        Transform::translate(Point::ORIGIN - pivot)
+            .then(self)
+            .then(&Transform::translate(pivot - Point::ORIGIN))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Transform::IDENTITY
+    }
+
+    pub fn apply(&self, point: Point) -> Point {
+        Point::new(
+            self.a * point.x + self.c * point.y + self.e,
+            self.b * point.x + self.d * point.y + self.f,
+        )
+    }
+
+    //压扁成一条线的变换没有逆
+    pub fn invert(&self) -> Option<Transform> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        Some(Transform {
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            e: (self.c * self.f - self.d * self.e) / det,
+            f: (self.b * self.e - self.a * self.f) / det,
+        })
+    }
+
+    //面积缩放比例的平方根，画布上用它近似线宽的缩放
+    pub fn scale_factor(&self) -> f32 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+
+    //只有旋转、翻转、平移和等比缩放时返回缩放比例，圆和椭圆可以直接改自己的参数
+    pub fn uniform_scale(&self) -> Option<f32> {
+        let (x, y) = (self.a.hypot(self.b), self.c.hypot(self.d));
+        let orthogonal = (self.a * self.c + self.b * self.d).abs() <= 1e-4 * x * y;
+        (x > f32::EPSILON && (x - y).abs() <= 1e-4 * x && orthogonal).then_some(x)
+    }
+
+    //变换方向，不带平移
+    pub fn apply_vector(&self, vector: Vector) -> Vector {
+        Vector::new(
+            self.a * vector.x + self.c * vector.y,
+            self.b * vector.x + self.d * vector.y,
+        )
+    }
+
+    //变换路径上的每个点，圆弧先换成贝塞尔曲线
+    pub fn apply_data(&self, data: &Data) -> Data {
+        let points = |p: &[f32]| {
+            p.chunks_exact(2)
+                .flat_map(|p| {
+                    let p = self.apply(Point::new(p[0], p[1]));
+                    [p.x, p.y]
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut current = Point::ORIGIN;
+        let mut start = Point::ORIGIN;
+        let mut result = Data::new();
+        for command in data.iter() {
+            let last = |p: &[f32]| match p {
+                [.., x, y] => Point::new(*x, *y),
+                _ => Point::ORIGIN,
+            };
+            result = match command {
+                Command::Move(_, p) => {
+                    current = last(p);
+                    start = current;
+                    result.move_to(points(p))
+                }
+                Command::Line(_, p) => {
+                    current = last(p);
+                    result.line_to(points(p))
+                }
+                Command::QuadraticCurve(_, p) => {
+                    current = last(p);
+                    result.quadratic_curve_to(points(p))
+                }
+                Command::CubicCurve(_, p) => {
+                    current = last(p);
+                    result.cubic_curve_to(points(p))
+                }
+                Command::EllipticalArc(_, p) if p.len() >= 7 => {
+                    let end = Point::new(p[5], p[6]);
+                    let cubics = arc_to_cubics(current, end, p[0], p[1], p[2], p[3], p[4])
+                        .into_iter()
+                        .flatten()
+                        .flat_map(|p| [p.x, p.y])
+                        .collect::<Vec<_>>();
+                    current = end;
+                    result.cubic_curve_to(points(&cubics))
+                }
+                Command::Close => {
+                    current = start;
+                    result.close()
+                }
+                _ => result,
+            };
+        }
+        result
+    }
+
+    pub fn export(&self) -> String {
+        format!(
+            "matrix({} {} {} {} {} {})",
+            self.a, self.b, self.c, self.d, self.e, self.f
+        )
+    }
+}
+
+//正在输入的数值，点应用时才生效
+#[derive(Debug, Clone)]
+pub struct TransformInput {
+    pub angle: String,
+    //百分比
+    pub scale_x: String,
+    pub scale_y: String,
+    pub skew_x: String,
+    pub skew_y: String,
+}
+
+impl Default for TransformInput {
+    fn default() -> Self {
+        TransformInput {
+            angle: "0".to_string(),
+            scale_x: "100".to_string(),
+            scale_y: "100".to_string(),
+            skew_x: "0".to_string(),
+            skew_y: "0".to_string(),
+        }
+    }
+}
+
+impl TransformInput {
+    pub fn update(&mut self, message: TransformMessage) {
+        match message {
+            TransformMessage::InputAngle(angle) => self.angle = angle,
+            TransformMessage::InputScaleX(x) => self.scale_x = x,
+            TransformMessage::InputScaleY(y) => self.scale_y = y,
+            TransformMessage::InputSkewX(x) => self.skew_x = x,
+            TransformMessage::InputSkewY(y) => self.skew_y = y,
+            _ => {}
+        }
+    }
+
+    //依次缩放、斜切、旋转，有一项无法解析或者缩放为0时不生效
+    pub fn transform(&self) -> Option<Transform> {
+        let parse = |input: &str| input.trim().parse::<f32>().ok();
+        let (x, y) = (parse(&self.scale_x)? / 100.0, parse(&self.scale_y)? / 100.0);
+        if x == 0.0 || y == 0.0 {
+            return None;
+        }
+        Some(
+            Transform::scale(x, y)
+                .then(&Transform::skew(parse(&self.skew_x)?, parse(&self.skew_y)?))
+                .then(&Transform::rotate(parse(&self.angle)?)),
+        )
+    }
+}
+
+//控制框上的把手，缩放的把手用-1、0、1表示在包围盒的左中右和上中下
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Handle {
+    Scale(i8, i8),
+    Rotate,
+}
+
+impl Handle {
+    //从from拖到to对应的变换，pivot在拖动的过程中不变
+    pub fn drag(&self, pivot: Point, from: Point, to: Point) -> Option<Transform> {
+        match self {
+            Handle::Scale(x, y) => {
+                let ratio = |from: f32, to: f32, pivot: f32| {
+                    if (from - pivot).abs() < 0.001 || (to - pivot).abs() < 0.001 {
+                        None
+                    } else {
+                        Some((to - pivot) / (from - pivot))
+                    }
+                };
+                let sx = if *x == 0 {
+                    1.0
+                } else {
+                    ratio(from.x, to.x, pivot.x)?
+                };
+                let sy = if *y == 0 {
+                    1.0
+                } else {
+                    ratio(from.y, to.y, pivot.y)?
+                };
+                Some(Transform::scale(sx, sy).about(pivot))
+            }
+            Handle::Rotate => {
+                let (a, b) = (from - pivot, to - pivot);
+                let angle = (a.x * b.y - a.y * b.x).atan2(a.x * b.x + a.y * b.y);
+                Some(Transform::rotate(angle.to_degrees()).about(pivot))
+            }
+        }
+    }
+}
+
+//选中的曲线外面的控制框，把手的大小不随画布缩放变化
+#[derive(Debug, Clone, Copy)]
+pub struct Gizmo {
+    pub bounds: Rectangle,
+    zoom: f32,
+}
+
+impl Gizmo {
+    const HANDLE_SIZE: f32 = 4.0;
+    //控制框比包围盒大一圈，免得把手挡住图形的点
+    const MARGIN: f32 = 8.0;
+    //旋转的把手在上边中点再往上
+    const ROTATE_OFFSET: f32 = 24.0;
+
+    pub fn new(bounds: Rectangle, zoom: f32) -> Self {
+        let margin = Gizmo::MARGIN / zoom;
+        Gizmo {
+            bounds: Rectangle {
+                x: bounds.x - margin,
+                y: bounds.y - margin,
+                width: bounds.width + margin * 2.0,
+                height: bounds.height + margin * 2.0,
+            },
+            zoom,
+        }
+    }
+
+    fn position(&self, x: i8, y: i8) -> Point {
+        let center = self.bounds.center();
+        Point::new(
+            center.x + self.bounds.width / 2.0 * x as f32,
+            center.y + self.bounds.height / 2.0 * y as f32,
+        )
+    }
+
+    pub fn handles(&self) -> Vec<(Handle, Point)> {
+        let mut handles = vec![];
+        for y in -1..=1 {
+            for x in -1..=1 {
+                if x != 0 || y != 0 {
+                    handles.push((Handle::Scale(x, y), self.position(x, y)));
+                }
+            }
+        }
+        handles.push((
+            Handle::Rotate,
+            self.position(0, -1) - Vector::new(0.0, Gizmo::ROTATE_OFFSET / self.zoom),
+        ));
+        handles
+    }
+
+    pub fn handle_at(&self, point: Point, distance: f32) -> Option<Handle> {
+        self.handles()
+            .into_iter()
+            .filter(|(_, p)| p.distance(point) < distance)
+            .min_by(|(_, a), (_, b)| a.distance(point).total_cmp(&b.distance(point)))
+            .map(|(handle, _)| handle)
+    }
+
+    //缩放时对边或对角不动，旋转时绕着中心
+    pub fn pivot(&self, handle: Handle) -> Point {
+        match handle {
+            Handle::Scale(x, y) => self.position(-x, -y),
+            Handle::Rotate => self.bounds.center(),
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let color = Color::from_rgb(0.2, 0.5, 1.0);
+        let stroke = Stroke::default().with_color(color);
+        let top = self.position(0, -1);
+        let handles = self.handles();
+        let size = Gizmo::HANDLE_SIZE / self.zoom;
+
+        frame.stroke(
+            &Path::new(|builder| {
+                builder.rectangle(self.bounds.position(), self.bounds.size());
+                builder.move_to(top);
+                builder.line_to(top - Vector::new(0.0, Gizmo::ROTATE_OFFSET / self.zoom));
+            }),
+            stroke,
+        );
+        for (handle, point) in handles {
+            let path = match handle {
+                Handle::Scale(..) => Path::rectangle(
+                    point - Vector::new(size, size),
+                    iced::Size::new(size * 2.0, size * 2.0),
+                ),
+                Handle::Rotate => Path::circle(point, size),
+            };
+            frame.fill(&path, Color::WHITE);
+            frame.stroke(&path, stroke);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_to(a: Point, b: Point) -> bool {
+        a.distance(b) < 1e-4
+    }
+
+    #[test]
+    fn then_applies_self_first() {
+        let t = Transform::scale(2.0, 2.0).then(&Transform::translate(Vector::new(1.0, 0.0)));
+        assert!(close_to(
+            t.apply(Point::new(1.0, 1.0)),
+            Point::new(3.0, 2.0)
+        ));
+
+        let t = Transform::translate(Vector::new(1.0, 0.0)).then(&Transform::scale(2.0, 2.0));
+        assert!(close_to(
+            t.apply(Point::new(1.0, 1.0)),
+            Point::new(4.0, 2.0)
+        ));
+    }
+
+    #[test]
+    fn rotate_is_clockwise() {
+        let p = Transform::rotate(90.0).apply(Point::new(1.0, 0.0));
+        assert!(close_to(p, Point::new(0.0, 1.0)));
+    }
+
+    #[test]
+    fn about_keeps_the_pivot() {
+        let pivot = Point::new(5.0, 7.0);
+        let t = Transform::rotate(30.0)
+            .then(&Transform::scale(2.0, 0.5))
+            .about(pivot);
+        assert!(close_to(t.apply(pivot), pivot));
+        let p = Transform::rotate(180.0)
+            .about(pivot)
+            .apply(Point::new(6.0, 7.0));
+        assert!(close_to(p, Point::new(4.0, 7.0)));
+    }
+
+    #[test]
+    fn invert_undoes_the_transform() {
+        let t = Transform::rotate(40.0)
+            .then(&Transform::skew(10.0, 0.0))
+            .then(&Transform::translate(Vector::new(3.0, -2.0)));
+        let inverse = t.invert().unwrap();
+        let p = Point::new(12.0, -5.0);
+        assert!(close_to(inverse.apply(t.apply(p)), p));
+        assert!(Transform::scale(0.0, 1.0).invert().is_none());
+    }
+
+    #[test]
+    fn uniform_scale_only_for_similarities() {
+        let t = Transform::rotate(30.0)
+            .then(&Transform::scale(-2.0, 2.0))
+            .then(&Transform::translate(Vector::new(5.0, 1.0)));
+        assert!((t.uniform_scale().unwrap() - 2.0).abs() < 1e-4);
+        assert_eq!(Transform::scale(2.0, 1.0).uniform_scale(), None);
+        assert_eq!(Transform::skew(20.0, 0.0).uniform_scale(), None);
+        assert_eq!(Transform::scale(0.0, 0.0).uniform_scale(), None);
+    }
+
+    #[test]
+    fn apply_data_turns_arcs_into_cubics() {
+        let data = Data::new()
+            .move_to(vec![0.0, 0.0])
+            .elliptical_arc_to(vec![10.0, 10.0, 0.0, 0.0, 1.0, 20.0, 0.0])
+            .close();
+        let t = Transform::translate(Vector::new(1.0, 2.0));
+        let result = t.apply_data(&data);
+
+        let mut end = None;
+        for command in result.iter() {
+            match command {
+                Command::Move(_, p) => assert_eq!(&p[..], &[1.0, 2.0]),
+                Command::CubicCurve(_, p) => {
+                    assert_eq!(p.len() % 6, 0);
+                    end = Some(Point::new(p[p.len() - 2], p[p.len() - 1]));
+                }
+                Command::Close => {}
+                _ => panic!("{:?}", command),
+            }
+        }
+        assert!(close_to(end.unwrap(), Point::new(21.0, 2.0)));
+    }
+}
@@ -1,6 +1,7 @@
-use iced::{Color, Point, Size};
+use iced::{canvas::Path, Color, Point, Size, Vector};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_with::{DeserializeAs, SerializeAs};
+use svg::node::element::path::{Command, Data};
 
 #[inline]
 pub fn get_size(left_top: Point, right_bottom: Point) -> Size {
@@ -19,26 +20,776 @@ pub fn get_radius(center: Point, point: Point) -> f32 {
 }
 
 #[inline]
-pub fn get_format_color(color: Color) -> String {
-    let mut r = format!("{:x}", (color.r * 255.0) as i32);
-    let mut g = format!("{:x}", (color.g * 255.0) as i32);
-    let mut b = format!("{:x}", (color.b * 255.0) as i32);
+pub fn midpoint(a: Point, b: Point) -> Point {
+    Point::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0)
+}
+
+//Ramer–Douglas–Peucker算法，去掉离首尾连线不超过tolerance的点
+pub fn simplify(points: &[Point], tolerance: f32) -> Vec<Point> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let (index, distance) = points[1..points.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(index, point)| (index + 1, distance_to_segment(*point, first, last)))
+        .fold((0, 0.0), |acc, x| if x.1 > acc.1 { x } else { acc });
+
+    if distance > tolerance {
+        let mut left = simplify(&points[..=index], tolerance);
+        left.pop();
+        left.extend(simplify(&points[index..], tolerance));
+        left
+    } else {
+        vec![first, last]
+    }
+}
+
+#[inline]
+pub fn distance_to_segment(point: Point, a: Point, b: Point) -> f32 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length_squared = dx * dx + dy * dy;
+    if length_squared == 0.0 {
+        return point.distance(a);
+    }
+
+    let t = (((point.x - a.x) * dx + (point.y - a.y) * dy) / length_squared).clamp(0.0, 1.0);
+    point.distance(Point::new(a.x + t * dx, a.y + t * dy))
+}
+
+//路径上的一个拐角，incoming和outgoing是进入和离开拐角的方向
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MiterJoin {
+    pub point: Point,
+    pub incoming: Vector,
+    pub outgoing: Vector,
+}
+
+impl MiterJoin {
+    fn unit(vector: Vector) -> Option<Vector> {
+        let length = (vector.x * vector.x + vector.y * vector.y).sqrt();
+        (length > f32::EPSILON).then(|| Vector::new(vector.x / length, vector.y / length))
+    }
+
+    //斜接长度与线宽之比，方向掉头时是无穷大
+    pub fn ratio(&self) -> f32 {
+        match (
+            MiterJoin::unit(self.incoming),
+            MiterJoin::unit(self.outgoing),
+        ) {
+            (Some(a), Some(b)) => {
+                let cos = a.x * b.x + a.y * b.y;
+                let half = ((1.0 + cos) / 2.0).max(0.0).sqrt();
+                if half > f32::EPSILON {
+                    1.0 / half
+                } else {
+                    f32::INFINITY
+                }
+            }
+            _ => 1.0,
+        }
+    }
+
+    //斜角连接缺掉的尖角：外侧的两个角和尖端，直线连接或者掉头时没有尖角
+    pub fn wedge(&self, half_width: f32) -> Option<[Point; 3]> {
+        let (a, b) = (
+            MiterJoin::unit(self.incoming)?,
+            MiterJoin::unit(self.outgoing)?,
+        );
+        let cross = a.x * b.y - a.y * b.x;
+        let ratio = self.ratio();
+        if cross.abs() <= f32::EPSILON || !ratio.is_finite() {
+            return None;
+        }
+
+        //往拐弯的反方向偏移的一侧是外侧
+        let side = if cross > 0.0 { -1.0 } else { 1.0 };
+        let (na, nb) = (
+            Vector::new(-a.y * side, a.x * side),
+            Vector::new(-b.y * side, b.x * side),
+        );
+        let bisector = MiterJoin::unit(na + nb)?;
+        Some([
+            self.point + na * half_width,
+            self.point + bisector * (half_width * ratio),
+            self.point + nb * half_width,
+        ])
+    }
+}
+
+//路径上所有的拐角，只考虑直线和贝塞尔曲线，圆弧两端当作光滑
+pub fn miter_joins(data: &Data) -> Vec<MiterJoin> {
+    let mut joins = vec![];
+    let mut current = Point::ORIGIN;
+    let mut start = Point::ORIGIN;
+    //进入当前点的方向和离开子路径起点的方向
+    let mut incoming: Option<Vector> = None;
+    let mut first: Option<Vector> = None;
 
-    if r.len() != 1 || g.len() != 1 || b.len() != 1 {
-        if r.len() == 1 {
-            r = ["0".to_string(), r].concat();
+    for command in data.iter() {
+        //这一段离开起点和到达终点时的方向
+        let (outgoing, arriving, end) = match command {
+            Command::Move(_, p) if p.len() >= 2 => {
+                current = Point::new(p[0], p[1]);
+                start = current;
+                incoming = None;
+                first = None;
+                continue;
+            }
+            Command::Line(_, p) if p.len() >= 2 => {
+                let end = Point::new(p[0], p[1]);
+                (Some(end - current), Some(end - current), end)
+            }
+            Command::QuadraticCurve(_, p) if p.len() >= 4 => {
+                let (control, end) = (Point::new(p[0], p[1]), Point::new(p[2], p[3]));
+                (Some(control - current), Some(end - control), end)
+            }
+            Command::CubicCurve(_, p) if p.len() >= 6 => {
+                let (c1, c2, end) = (
+                    Point::new(p[0], p[1]),
+                    Point::new(p[2], p[3]),
+                    Point::new(p[4], p[5]),
+                );
+                (Some(c1 - current), Some(end - c2), end)
+            }
+            Command::EllipticalArc(_, p) if p.len() >= 7 => (None, None, Point::new(p[5], p[6])),
+            Command::Close => {
+                if current != start {
+                    let closing = start - current;
+                    if let Some(incoming) = incoming {
+                        joins.push(MiterJoin {
+                            point: current,
+                            incoming,
+                            outgoing: closing,
+                        });
+                    }
+                    incoming = Some(closing);
+                }
+                if let (Some(incoming), Some(first)) = (incoming, first) {
+                    joins.push(MiterJoin {
+                        point: start,
+                        incoming,
+                        outgoing: first,
+                    });
+                }
+                current = start;
+                incoming = None;
+                first = None;
+                continue;
+            }
+            _ => continue,
+        };
+
+        if let (Some(incoming), Some(outgoing)) = (incoming, outgoing) {
+            joins.push(MiterJoin {
+                point: current,
+                incoming,
+                outgoing,
+            });
         }
-        if g.len() == 1 {
-            g = ["0".to_string(), g].concat();
+        if current == start && first.is_none() {
+            first = outgoing;
         }
-        if b.len() == 1 {
-            b = ["0".to_string(), b].concat();
+        incoming = arriving;
+        current = end;
+    }
+
+    joins
+}
+
+//路径的包围盒，控制点也算在内，圆弧取采样点
+pub fn path_bounds(data: &Data) -> Option<(Point, Point)> {
+    let mut points = vec![];
+    let mut current = Point::ORIGIN;
+    let mut start = Point::ORIGIN;
+
+    for command in data.iter() {
+        match command {
+            Command::Move(_, p) if p.len() >= 2 => {
+                current = Point::new(p[0], p[1]);
+                start = current;
+                points.push(current);
+            }
+            Command::Line(_, p) | Command::QuadraticCurve(_, p) | Command::CubicCurve(_, p) => {
+                points.extend(p.chunks_exact(2).map(|p| Point::new(p[0], p[1])));
+                if let Some(end) = points.last() {
+                    current = *end;
+                }
+            }
+            Command::EllipticalArc(_, p) if p.len() >= 7 => {
+                let end = Point::new(p[5], p[6]);
+                points.extend(sample_arc(current, end, p[0], p[1], p[2], p[3], p[4]));
+                current = end;
+            }
+            Command::Close => current = start,
+            _ => {}
+        }
+    }
+
+    let min = points
+        .iter()
+        .copied()
+        .reduce(|a, b| Point::new(a.x.min(b.x), a.y.min(b.y)))?;
+    let max = points
+        .iter()
+        .copied()
+        .reduce(|a, b| Point::new(a.x.max(b.x), a.y.max(b.y)))?;
+    Some((min, max))
+}
+
+//路径上直线段的中点，包括闭合时连回起点的那一段
+pub fn segment_midpoints(data: &Data) -> Vec<Point> {
+    let mut midpoints = vec![];
+    let mut current = Point::ORIGIN;
+    let mut start = Point::ORIGIN;
+
+    for command in data.iter() {
+        match command {
+            Command::Move(_, p) if p.len() >= 2 => {
+                current = Point::new(p[0], p[1]);
+                start = current;
+            }
+            Command::Line(_, p) => {
+                for p in p.chunks_exact(2) {
+                    let to = Point::new(p[0], p[1]);
+                    midpoints.push(midpoint(current, to));
+                    current = to;
+                }
+            }
+            Command::QuadraticCurve(_, p)
+            | Command::CubicCurve(_, p)
+            | Command::EllipticalArc(_, p) => {
+                if let [.., x, y] = p[..] {
+                    current = Point::new(x, y);
+                }
+            }
+            Command::Close => {
+                if current != start {
+                    midpoints.push(midpoint(current, start));
+                }
+                current = start;
+            }
+            _ => {}
+        }
+    }
+
+    midpoints
+}
+
+//svg圆弧换算成的圆心参数，theta是起始角，delta是扫过的角度
+struct ArcCenter {
+    center: Point,
+    rx: f32,
+    ry: f32,
+    sin: f32,
+    cos: f32,
+    theta: f32,
+    delta: f32,
+}
+
+impl ArcCenter {
+    fn point(&self, angle: f32) -> Point {
+        let (s, c) = angle.sin_cos();
+        Point::new(
+            self.center.x + self.rx * self.cos * c - self.ry * self.sin * s,
+            self.center.y + self.rx * self.sin * c + self.ry * self.cos * s,
+        )
+    }
+
+    //切线方向，长度和角度的变化对应
+    fn tangent(&self, angle: f32) -> Vector {
+        let (s, c) = angle.sin_cos();
+        Vector::new(
+            -self.rx * self.cos * s - self.ry * self.sin * c,
+            -self.rx * self.sin * s + self.ry * self.cos * c,
+        )
+    }
+}
+
+//把svg圆弧的端点参数换算成圆心参数，半径为0时退化成直线
+fn arc_center(
+    from: Point,
+    to: Point,
+    rx: f32,
+    ry: f32,
+    rotation: f32,
+    large_arc: f32,
+    sweep: f32,
+) -> Option<ArcCenter> {
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == 0.0 || ry == 0.0 {
+        return None;
+    }
+
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let (dx, dy) = ((from.x - to.x) / 2.0, (from.y - to.y) / 2.0);
+    let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+
+    //半径不够连接两个端点时按比例放大
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coefficient = if denominator == 0.0 {
+        0.0
+    } else {
+        (numerator / denominator).max(0.0).sqrt()
+    };
+    if (large_arc != 0.0) == (sweep != 0.0) {
+        coefficient = -coefficient;
+    }
+    let (cx1, cy1) = (coefficient * rx * y1 / ry, -coefficient * ry * x1 / rx);
+    let center = Point::new(
+        cos * cx1 - sin * cy1 + (from.x + to.x) / 2.0,
+        sin * cx1 + cos * cy1 + (from.y + to.y) / 2.0,
+    );
+
+    let angle = |u: Vector, v: Vector| (u.x * v.y - u.y * v.x).atan2(u.x * v.x + u.y * v.y);
+    let u = Vector::new((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let v = Vector::new((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+    let theta = angle(Vector::new(1.0, 0.0), u);
+    let mut delta = angle(u, v);
+    if sweep == 0.0 && delta > 0.0 {
+        delta -= std::f32::consts::TAU;
+    } else if sweep != 0.0 && delta < 0.0 {
+        delta += std::f32::consts::TAU;
+    }
+
+    Some(ArcCenter {
+        center,
+        rx,
+        ry,
+        sin,
+        cos,
+        theta,
+        delta,
+    })
+}
+
+//沿着圆弧取点
+fn sample_arc(
+    from: Point,
+    to: Point,
+    rx: f32,
+    ry: f32,
+    rotation: f32,
+    large_arc: f32,
+    sweep: f32,
+) -> Vec<Point> {
+    const SAMPLES: usize = 16;
+    match arc_center(from, to, rx, ry, rotation, large_arc, sweep) {
+        Some(arc) => (0..=SAMPLES)
+            .map(|i| arc.point(arc.theta + arc.delta * i as f32 / SAMPLES as f32))
+            .collect(),
+        None => vec![to],
+    }
+}
+
+//把圆弧拆成每段不超过90度的三次贝塞尔曲线，返回每段的两个控制点和终点
+pub fn arc_to_cubics(
+    from: Point,
+    to: Point,
+    rx: f32,
+    ry: f32,
+    rotation: f32,
+    large_arc: f32,
+    sweep: f32,
+) -> Vec<[Point; 3]> {
+    let arc = match arc_center(from, to, rx, ry, rotation, large_arc, sweep) {
+        Some(arc) => arc,
+        None => return vec![[from, to, to]],
+    };
+
+    let segments = (arc.delta.abs() / std::f32::consts::FRAC_PI_2)
+        .ceil()
+        .max(1.0) as usize;
+    let step = arc.delta / segments as f32;
+    let k = 4.0 / 3.0 * (step / 4.0).tan();
+    (0..segments)
+        .map(|i| {
+            let (a, b) = (
+                arc.theta + step * i as f32,
+                arc.theta + step * (i + 1) as f32,
+            );
+            let end = if i + 1 == segments { to } else { arc.point(b) };
+            [
+                arc.point(a) + arc.tangent(a) * k,
+                arc.point(b) - arc.tangent(b) * k,
+                end,
+            ]
+        })
+        .collect()
+}
+
+//把svg的路径数据转成画布上的路径，圆弧换成贝塞尔曲线
+pub fn data_to_path(data: &Data) -> Path {
+    Path::new(|builder| {
+        let mut current = Point::ORIGIN;
+        let mut start = Point::ORIGIN;
+        for command in data.iter() {
+            match command {
+                Command::Move(_, p) if p.len() >= 2 => {
+                    current = Point::new(p[0], p[1]);
+                    start = current;
+                    builder.move_to(current);
+                }
+                Command::Line(_, p) => {
+                    for p in p.chunks_exact(2) {
+                        current = Point::new(p[0], p[1]);
+                        builder.line_to(current);
+                    }
+                }
+                Command::QuadraticCurve(_, p) => {
+                    for p in p.chunks_exact(4) {
+                        current = Point::new(p[2], p[3]);
+                        builder.quadratic_curve_to(Point::new(p[0], p[1]), current);
+                    }
+                }
+                Command::CubicCurve(_, p) => {
+                    for p in p.chunks_exact(6) {
+                        current = Point::new(p[4], p[5]);
+                        builder.bezier_curve_to(
+                            Point::new(p[0], p[1]),
+                            Point::new(p[2], p[3]),
+                            current,
+                        );
+                    }
+                }
+                Command::EllipticalArc(_, p) if p.len() >= 7 => {
+                    let end = Point::new(p[5], p[6]);
+                    for [a, b, to] in arc_to_cubics(current, end, p[0], p[1], p[2], p[3], p[4]) {
+                        builder.bezier_curve_to(a, b, to);
+                    }
+                    current = end;
+                }
+                Command::Close => {
+                    builder.close();
+                    current = start;
+                }
+                _ => {}
+            }
         }
+    })
+}
+
+//写入svg文本节点和属性之前需要转义，svg库不会转义属性值，同时带单双引号的属性会被丢掉
+pub fn escape_xml(content: &str) -> String {
+    content
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+//属性保留两位小数，去掉多余的0
+pub fn format_attribute(value: f32) -> String {
+    let formatted = format!("{:.2}", value);
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+//支持#rgb、#rrggbb和#rrggbbaa
+pub fn parse_hex_color(hex: &str) -> Option<Color> {
+    let hex = hex.trim().strip_prefix('#')?;
+    if !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize, len: usize| {
+        u8::from_str_radix(&hex[i * len..(i + 1) * len].repeat(3 - len), 16)
+            .ok()
+            .map(|c| c as f32 / 255.0)
+    };
+
+    match hex.len() {
+        3 => Some(Color::from_rgb(
+            channel(0, 1)?,
+            channel(1, 1)?,
+            channel(2, 1)?,
+        )),
+        6 => Some(Color::from_rgb(
+            channel(0, 2)?,
+            channel(1, 2)?,
+            channel(2, 2)?,
+        )),
+        8 => Some(Color::from_rgba(
+            channel(0, 2)?,
+            channel(1, 2)?,
+            channel(2, 2)?,
+            channel(3, 2)?,
+        )),
+        _ => None,
+    }
+}
+
+//四舍五入，保证和parse_hex_color互为逆运算
+fn into_rgba8(color: Color) -> [u8; 4] {
+    [color.r, color.g, color.b, color.a].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+//svg里透明度单独写在*-opacity里，这里只输出#rrggbb
+#[inline]
+pub fn get_format_rgb(color: Color) -> String {
+    let [r, g, b, _] = into_rgba8(color);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+//不透明时是#rrggbb，否则是#rrggbbaa，能被parse_hex_color原样解析回来
+#[inline]
+pub fn get_format_color(color: Color) -> String {
+    let [r, g, b, a] = into_rgba8(color);
+    if a == u8::MAX {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
+
+//十六进制或者css的颜色名
+pub fn parse_color(input: &str) -> Option<Color> {
+    parse_hex_color(input).or_else(|| named_color(input))
+}
+
+pub fn named_color(name: &str) -> Option<Color> {
+    let name = name.trim().to_ascii_lowercase();
+    if name == "transparent" {
+        return Some(Color::TRANSPARENT);
     }
+    NAMED_COLORS
+        .binary_search_by(|(n, _)| n.cmp(&name.as_str()))
+        .ok()
+        .map(|i| {
+            let [_, r, g, b] = NAMED_COLORS[i].1.to_be_bytes();
+            Color::from_rgb8(r, g, b)
+        })
+}
+
+//h是角度，s和l在0到1之间
+pub fn to_hsl(color: Color) -> (f32, f32, f32) {
+    let (h, max, min) = hue(color);
+    let l = (max + min) / 2.0;
+    let s = if max == min {
+        0.0
+    } else {
+        (max - min) / (1.0 - (2.0 * l - 1.0).abs())
+    };
+    (h, s, l)
+}
+
+pub fn from_hsl(h: f32, s: f32, l: f32, a: f32) -> Color {
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    from_chroma(h, chroma, l - chroma / 2.0, a)
+}
 
-    ["#".to_string(), r, g, b].concat()
+pub fn to_hsv(color: Color) -> (f32, f32, f32) {
+    let (h, max, min) = hue(color);
+    let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+    (h, s, max)
 }
 
+pub fn from_hsv(h: f32, s: f32, v: f32, a: f32) -> Color {
+    let chroma = v * s;
+    from_chroma(h, chroma, v - chroma, a)
+}
+
+//色相以及rgb的最大值和最小值
+fn hue(color: Color) -> (f32, f32, f32) {
+    let Color { r, g, b, .. } = color;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let h = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (h, max, min)
+}
+
+fn from_chroma(h: f32, chroma: f32, m: f32, a: f32) -> Color {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    Color::from_rgba(
+        (r + m).clamp(0.0, 1.0),
+        (g + m).clamp(0.0, 1.0),
+        (b + m).clamp(0.0, 1.0),
+        a,
+    )
+}
+
+//按名字排好序，用二分查找
+const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
 #[derive(Serialize, Deserialize)]
 #[serde(remote = "Point")]
 pub struct SerdePoint {
@@ -115,3 +866,87 @@ impl<'de> DeserializeAs<'de, Size> for SerdeSize {
         SerdeSize::deserialize(deserializer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polyline(points: &[(f32, f32)], closed: bool) -> Data {
+        let mut data = Data::new().move_to(vec![points[0].0, points[0].1]);
+        for (x, y) in &points[1..] {
+            data = data.line_to(vec![*x, *y]);
+        }
+        if closed {
+            data = data.close();
+        }
+        data
+    }
+
+    fn close_to(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn right_angle_miter_ratio_is_sqrt_2() {
+        let joins = miter_joins(&polyline(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)], false));
+        assert_eq!(joins.len(), 1);
+        assert_eq!(joins[0].point, Point::new(10.0, 0.0));
+        assert!(close_to(joins[0].ratio(), 2f32.sqrt()));
+    }
+
+    #[test]
+    fn straight_and_reversed_joins() {
+        let straight = miter_joins(&polyline(&[(0.0, 0.0), (5.0, 0.0), (10.0, 0.0)], false));
+        assert!(close_to(straight[0].ratio(), 1.0));
+        assert_eq!(straight[0].wedge(1.0), None);
+
+        let reversed = miter_joins(&polyline(&[(0.0, 0.0), (10.0, 0.0), (0.0, 0.0)], false));
+        assert!(reversed[0].ratio().is_infinite());
+        assert_eq!(reversed[0].wedge(1.0), None);
+    }
+
+    #[test]
+    fn closed_path_has_a_join_at_every_corner() {
+        let square = polyline(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)], true);
+        let joins = miter_joins(&square);
+        assert_eq!(joins.len(), 4);
+        assert!(joins.iter().any(|join| join.point == Point::ORIGIN));
+        assert!(joins.iter().all(|join| close_to(join.ratio(), 2f32.sqrt())));
+    }
+
+    #[test]
+    fn zero_length_segments_are_smooth() {
+        let joins = miter_joins(&polyline(&[(0.0, 0.0), (0.0, 0.0), (10.0, 0.0)], false));
+        assert!(joins.iter().all(|join| close_to(join.ratio(), 1.0)));
+    }
+
+    #[test]
+    fn wedge_tip_is_on_the_outside() {
+        //向右再向下，外侧在右上方
+        let joins = miter_joins(&polyline(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)], false));
+        let [a, tip, b] = joins[0].wedge(1.0).unwrap();
+        assert!(close_to(a.x, 10.0) && close_to(a.y, -1.0));
+        assert!(close_to(b.x, 11.0) && close_to(b.y, 0.0));
+        assert!(close_to(tip.x, 11.0) && close_to(tip.y, -1.0));
+    }
+
+    #[test]
+    fn escape_xml_handles_markup_and_quotes() {
+        assert_eq!(
+            escape_xml(r#"a & <b> "c" 'd'"#),
+            "a &amp; &lt;b&gt; &quot;c&quot; &apos;d&apos;"
+        );
+    }
+
+    #[test]
+    fn bezier_joins_use_control_point_tangents() {
+        let data = Data::new()
+            .move_to(vec![0.0, 0.0])
+            .quadratic_curve_to(vec![10.0, 0.0, 10.0, 10.0])
+            .line_to(vec![0.0, 10.0]);
+        let joins = miter_joins(&data);
+        assert_eq!(joins.len(), 1);
+        assert_eq!(joins[0].incoming, Vector::new(0.0, 10.0));
+        assert_eq!(joins[0].outgoing, Vector::new(-10.0, 0.0));
+    }
+}
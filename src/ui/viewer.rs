@@ -1,9 +1,10 @@
 use std::path::PathBuf;
 
+use super::eyedropper::Eyedropper;
 use super::style;
 use iced::pure::widget::{Button, Column, Container, Image, Row, Text};
 use iced::pure::Element;
-use iced::{Alignment, Length, Svg};
+use iced::{Alignment, Color, Length, Svg};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ViewerMessage {
@@ -11,6 +12,8 @@ pub enum ViewerMessage {
     Navigate(i32),
     CloseNotFound,
     JumpToImage(usize),
+    //吸管取到的像素坐标
+    PixelPicked(u32, u32),
 }
 
 #[derive(Debug, Default, Clone)]
@@ -19,6 +22,8 @@ pub struct Viewer {
     pub images: Vec<PathBuf>,
     pub on_view: Option<usize>,
     pub on_preview: Option<(usize, usize)>,
+    //编辑界面的吸管正在从图片上取色
+    pub picking: bool,
 }
 
 impl Viewer {
@@ -49,6 +54,8 @@ impl Viewer {
                 }
                 self.update_preview();
             }
+            //由上层截获并交给编辑界面
+            ViewerMessage::PixelPicked(..) => {}
         }
     }
 
@@ -66,6 +73,12 @@ impl Viewer {
                 let current_image = self.images[index].as_path();
                 let image_column = if current_image.exists() {
                     match current_image.extension() {
+                        Some(e) if (e.eq("png") || e.eq("jpg")) && self.picking => Column::new()
+                            .push(Text::new("Click on the image to pick a color."))
+                            .push(
+                                Eyedropper::new(current_image, ViewerMessage::PixelPicked)
+                                    .height(Length::FillPortion(11)),
+                            ),
                         Some(e) if e.eq("png") || e.eq("jpg") => Column::new()
                             .push(Image::new(current_image).height(Length::FillPortion(11))),
                         Some(e) if e.eq("svg") => Column::new()
@@ -171,6 +184,18 @@ impl Viewer {
         self.update_preview();
     }
 
+    //当前图片上某个像素的颜色，svg不支持取色
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        let path = self.images.get(self.on_view?)?;
+        let image = image::open(path).ok()?.to_rgba8();
+        if x < image.width() && y < image.height() {
+            let [r, g, b, a] = image.get_pixel(x, y).0;
+            Some(Color::from_rgba8(r, g, b, a as f32 / 255.0))
+        } else {
+            None
+        }
+    }
+
     #[inline]
     pub fn close(&mut self) {
         if let Some(index) = &mut self.on_view {
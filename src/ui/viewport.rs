@@ -0,0 +1,90 @@
+use iced::{Point, Rectangle, Size, Vector};
+
+#[derive(Debug, Clone)]
+pub enum ViewMessage {
+    ZoomIn,
+    ZoomOut,
+    //缩放到刚好放下所有曲线
+    Fit,
+    //恢复到100%
+    Actual,
+}
+
+//画布的视图，文档里的点乘上缩放再加上偏移得到画布上的点
+#[derive(Debug, Clone, Copy)]
+pub struct Viewport {
+    pub zoom: f32,
+    pub offset: Vector,
+    //画布的大小，画布每次更新时记下来
+    pub size: Size,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport {
+            zoom: 1.0,
+            offset: Vector::new(0.0, 0.0),
+            size: Size::ZERO,
+        }
+    }
+}
+
+impl Viewport {
+    pub const MIN_ZOOM: f32 = 0.05;
+    pub const MAX_ZOOM: f32 = 64.0;
+    //按钮和快捷键每次缩放的倍数
+    pub const STEP: f32 = 1.25;
+    //适应内容时四周留出的空白
+    const PADDING: f32 = 20.0;
+
+    pub fn to_document(self, point: Point) -> Point {
+        Point::new(
+            (point.x - self.offset.x) / self.zoom,
+            (point.y - self.offset.y) / self.zoom,
+        )
+    }
+
+    //以画布上的anchor为中心缩放，anchor下面的文档位置不变
+    pub fn zoom_at(&mut self, factor: f32, anchor: Point) {
+        let fixed = self.to_document(anchor);
+        self.zoom = (self.zoom * factor).clamp(Viewport::MIN_ZOOM, Viewport::MAX_ZOOM);
+        self.offset = anchor - Point::new(fixed.x * self.zoom, fixed.y * self.zoom);
+    }
+
+    pub fn pan(&mut self, delta: Vector) {
+        self.offset = self.offset + delta;
+    }
+
+    fn center(&self) -> Point {
+        Point::new(self.size.width / 2.0, self.size.height / 2.0)
+    }
+
+    //没有内容时回到原点
+    pub fn update(&mut self, message: ViewMessage, content: Option<Rectangle>) {
+        match message {
+            ViewMessage::ZoomIn => self.zoom_at(Viewport::STEP, self.center()),
+            ViewMessage::ZoomOut => self.zoom_at(1.0 / Viewport::STEP, self.center()),
+            ViewMessage::Actual => self.zoom_at(1.0 / self.zoom, self.center()),
+            ViewMessage::Fit => match content {
+                Some(content) if content.width > 0.0 || content.height > 0.0 => {
+                    let available = Size::new(
+                        (self.size.width - Viewport::PADDING * 2.0).max(1.0),
+                        (self.size.height - Viewport::PADDING * 2.0).max(1.0),
+                    );
+                    self.zoom = (available.width / content.width.max(f32::EPSILON))
+                        .min(available.height / content.height.max(f32::EPSILON))
+                        .clamp(Viewport::MIN_ZOOM, Viewport::MAX_ZOOM);
+                    let center = content.center();
+                    self.offset =
+                        self.center() - Point::new(center.x * self.zoom, center.y * self.zoom);
+                }
+                _ => {
+                    *self = Viewport {
+                        size: self.size,
+                        ..Viewport::default()
+                    }
+                }
+            },
+        }
+    }
+}